@@ -1,30 +1,123 @@
 use anyhow::anyhow;
+use chrono::{Duration, Local, NaiveDate};
 use clap::Parser;
 use dialoguer::Password;
 use url::Url;
 
 use crate::redmine;
 use crate::track;
-use crate::track::Config;
+use crate::track::duration::HoursInput;
+use crate::track::format::Format;
+use crate::track::period::Period;
+use crate::track::{Config, SearchFilter, StatusFilter};
 
 #[derive(Parser, Debug, Clone)]
 #[clap(name = "track", about = "Track your time with redmine.")]
 pub struct Cli {
     #[arg(long = "yesterday", short = 'y', help = "Create entry for yesterday.")]
     yesterday: bool,
-    #[arg(help = "Create entry for specified id.")]
+    #[arg(
+        long = "date",
+        short = 'd',
+        help = "Create entry for the given date, e.g. 2024-03-18, -3, monday or 'last friday'.",
+        value_parser = parse_date,
+        allow_hyphen_values = true,
+        conflicts_with = "yesterday"
+    )]
+    date: Option<NaiveDate>,
+    #[arg(long = "force", help = "Allow creating entries in the future.")]
+    force: bool,
+    #[arg(
+        help = "Create entry for an issue like 1234, #1234 or its URL, or a project like @identifier.",
+        conflicts_with = "project"
+    )]
     id: Option<String>,
+    #[command(flatten)]
+    preset: PresetArgs,
     #[command(subcommand)]
     command: Option<Command>,
 }
 
+#[derive(Parser, Debug, Clone)]
+struct PresetArgs {
+    /// The hours to book, like 1.5, 1:30 or 1h30m, instead of asking for
+    /// them. An adjustment like +15m is applied to the time ranges in the
+    /// comment.
+    #[arg(long = "hours", allow_hyphen_values = true)]
+    hours: Option<HoursInput>,
+
+    /// The comment of the time entry, instead of asking for it.
+    #[arg(long = "comment")]
+    comment: Option<String>,
+
+    /// The name or id of the activity, instead of asking for it.
+    #[arg(long = "activity")]
+    activity: Option<String>,
+
+    /// The identifier or id of the project to book on, instead of an issue.
+    #[arg(long = "project")]
+    project: Option<String>,
+
+    /// A custom field value as name=value, may be repeated.
+    #[arg(long = "field", value_parser = parse_field)]
+    fields: Vec<(String, String)>,
+
+    /// Never ask for anything, fail if values are missing instead.
+    #[arg(long = "no-input")]
+    no_input: bool,
+}
+
+impl From<PresetArgs> for track::Preset {
+    fn from(args: PresetArgs) -> Self {
+        track::Preset {
+            hours: args.hours,
+            comment: args.comment,
+            activity: args.activity,
+            project: args.project,
+            fields: args.fields,
+            no_input: args.no_input,
+        }
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    track::date::parse(value, Local::now().date_naive())
+}
+
+fn parse_field(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+        .ok_or(format!(
+            "The field {} should have the form name=value.",
+            value
+        ))
+}
+
 #[derive(Parser, Debug, Clone)]
 enum Command {
     #[command(name = "search", about = "Search for tickets")]
     Search {
         query: String,
         #[arg(long = "direct_track", short = 't', help = "If only one Issue is found, start track.", default_value = "false")]
-        direct_track: bool
+        direct_track: bool,
+        #[arg(long = "format", short = 'f', value_enum, default_value_t = Format::Table, help = "The output format.")]
+        format: Format,
+        /// Choose one of the results and track it right away.
+        #[arg(long = "interactive", short = 'i', conflicts_with = "format")]
+        interactive: bool,
+        /// Only find issues of the project with this identifier or id.
+        #[arg(long = "project", short = 'p')]
+        project: Option<String>,
+        /// Only find open or closed issues.
+        #[arg(long = "status", short = 's', value_enum, default_value_t = StatusFilter::All)]
+        status: StatusFilter,
+        /// Only find issues assigned to me, a user id or part of a name.
+        #[arg(long = "assignee", short = 'a')]
+        assignee: Option<String>,
+        /// Search the issues cached by `track issues sync` instead of Redmine.
+        #[arg(long = "offline", short = 'o')]
+        offline: bool,
     },
     #[command(name = "login", about = "Login to your account.")]
     Login {
@@ -39,9 +132,74 @@ enum Command {
     },
     #[command(
         name = "list",
-        about = "List your time entries for today, yesterday or this week."
+        about = "List your time entries for a day, week, month or any other period."
     )]
     List(ListArgs),
+    #[command(
+        name = "fields",
+        about = "Discover and list the custom fields of time entries."
+    )]
+    Fields {
+        #[command(subcommand)]
+        command: FieldsCommand,
+    },
+    #[command(name = "issues", about = "Cache your issues for searching offline.")]
+    Issues {
+        #[command(subcommand)]
+        command: IssuesCommand,
+    },
+    #[command(name = "start", about = "Start a timer for an issue or project.")]
+    Start {
+        #[arg(help = "Start the timer for an issue like 1234, #1234 or its URL, or a project like @identifier.")]
+        id: Option<String>,
+        #[arg(
+            long = "comment",
+            short = 'c',
+            help = "The comment for the time entry."
+        )]
+        comment: Option<String>,
+    },
+    #[command(name = "stop", about = "Stop the running timer and create a time entry.")]
+    Stop,
+    #[command(name = "status", about = "Show the running timer.")]
+    Status,
+    #[command(name = "cancel", about = "Discard the running timer.")]
+    Cancel,
+    #[command(name = "undo", about = "Delete the time entry, that was created last.")]
+    Undo,
+    #[command(name = "edit", about = "Edit an existing time entry.")]
+    Edit {
+        #[arg(help = "The id of the time entry, as shown by `track list`.")]
+        id: i32,
+    },
+    #[command(name = "delete", about = "Delete an existing time entry.")]
+    Delete {
+        #[arg(help = "The id of the time entry, as shown by `track list`.")]
+        id: i32,
+    },
+}
+
+#[derive(Parser, Debug, Clone)]
+enum IssuesCommand {
+    #[command(
+        name = "sync",
+        about = "Refresh the issues updated since the last sync in ~/.track_issues."
+    )]
+    Sync,
+}
+
+#[derive(Parser, Debug, Clone)]
+enum FieldsCommand {
+    #[command(
+        name = "sync",
+        about = "Discover the custom fields of time entries and store them in ~/.track."
+    )]
+    Sync,
+    #[command(name = "list", about = "List the known custom fields of time entries.")]
+    List {
+        #[arg(long = "format", short = 'f', value_enum, default_value_t = Format::Table, help = "The output format.")]
+        format: Format,
+    },
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -51,16 +209,83 @@ struct ListArgs {
     with_issues: bool,
 
     /// Show time entries from the previous week or day.
-    #[arg(long = "previous", short = 'p')]
+    #[arg(long = "previous", short = 'p', conflicts_with = "offset")]
     previous: bool,
 
-    /// Show a summary of the weekly activity.
-    #[arg(long = "week", short = 'w')]
-    week: bool,
+    /// Show time entries from N days, weeks or months back.
+    #[arg(long = "offset", short = 'o', value_name = "N")]
+    offset: Option<u32>,
+
+    /// Show a summary of the weekly activity, optionally of a week like 2024-W12.
+    #[arg(long = "week", short = 'w', value_name = "WEEK", num_args = 0..=1)]
+    week: Option<Option<String>>,
+
+    /// Show a summary of the monthly activity, optionally of a month like 2024-03.
+    #[arg(
+        long = "month",
+        short = 'm',
+        value_name = "MONTH",
+        num_args = 0..=1,
+        conflicts_with = "week"
+    )]
+    month: Option<Option<String>>,
+
+    /// Show a summary starting at the given date.
+    #[arg(
+        long = "from",
+        value_parser = parse_date,
+        allow_hyphen_values = true,
+        conflicts_with_all = ["week", "month"]
+    )]
+    from: Option<NaiveDate>,
+
+    /// Show a summary ending at the given date, defaults to today.
+    #[arg(
+        long = "to",
+        value_parser = parse_date,
+        allow_hyphen_values = true,
+        requires = "from"
+    )]
+    to: Option<NaiveDate>,
 
     /// Ignore time entries that have the specified custom field name.
     #[arg(long = "ignore")]
     ignore_custom_field: Option<String>,
+
+    /// The output format.
+    #[arg(long = "format", short = 'f', value_enum, default_value_t = Format::Table)]
+    format: Format,
+}
+
+impl ListArgs {
+    /// Returns the period to list, based on the given arguments.
+    fn period(&self) -> Result<Period, String> {
+        let today = Local::now().date_naive();
+        let period = match (&self.week, &self.month, self.from) {
+            (Some(Some(week)), _, _) => Period::parse_week(week)?,
+            (Some(None), _, _) => Period::week(today),
+            (_, Some(Some(month)), _) => Period::parse_month(month)?,
+            (_, Some(None), _) => Period::month(today),
+            (_, _, Some(from)) => {
+                let to = self.to.unwrap_or(today);
+                if from > to {
+                    return Err(format!(
+                        "The period from {} to {} ends before it starts.",
+                        from, to
+                    ));
+                }
+                Period::Range(from, to)
+            }
+            _ => Period::Day(today),
+        };
+
+        let offset = match self.previous {
+            true => 1,
+            false => self.offset.unwrap_or(0),
+        };
+
+        period.back(offset)
+    }
 }
 
 pub fn run(cli: Cli, config: Option<Config>) -> Result<(), anyhow::Error> {
@@ -81,15 +306,88 @@ pub fn run(cli: Cli, config: Option<Config>) -> Result<(), anyhow::Error> {
         )),
         (None, Some(config)) => {
             let client = redmine::request::Client::new(config);
-            track::track(&client, cli.yesterday, cli.id)
+            let today = Local::now().date_naive();
+            let spent_on = match (cli.yesterday, cli.date) {
+                (true, _) => today - Duration::days(1),
+                (false, Some(date)) => date,
+                (false, None) => today,
+            };
+            track::track(&client, spent_on, cli.force, cli.id, &cli.preset.into())
         }
         (Some(Command::List(args)), Some(config)) => {
             let client = redmine::request::Client::new(config);
-            track::list(&client, args.with_issues, args.previous, args.week, args.ignore_custom_field)
+            let period = args.period().map_err(|err| anyhow!(err))?;
+            track::list(
+                &client,
+                period,
+                args.with_issues,
+                args.ignore_custom_field,
+                args.format,
+            )
+        }
+        (
+            Some(Command::Search {
+                query,
+                direct_track,
+                format,
+                interactive,
+                project,
+                status,
+                assignee,
+                offline,
+            }),
+            Some(config),
+        ) => {
+            let client = redmine::request::Client::new(config);
+            let filter = SearchFilter {
+                project,
+                status,
+                assignee,
+            };
+            track::search(
+                &client,
+                query,
+                &filter,
+                offline,
+                direct_track,
+                interactive,
+                format,
+            )
+        }
+        (Some(Command::Fields { command }), Some(config)) => {
+            let client = redmine::request::Client::new(config);
+            match command {
+                FieldsCommand::Sync => track::sync_fields(&client),
+                FieldsCommand::List { format } => track::list_fields(&client, format),
+            }
+        }
+        (Some(Command::Issues { command }), Some(config)) => {
+            let client = redmine::request::Client::new(config);
+            match command {
+                IssuesCommand::Sync => track::sync_issues(&client),
+            }
+        }
+        (Some(Command::Start { id, comment }), Some(config)) => {
+            let client = redmine::request::Client::new(config);
+            track::start(&client, id, comment)
+        }
+        (Some(Command::Stop), Some(config)) => {
+            let client = redmine::request::Client::new(config);
+            track::stop(&client)
+        }
+        (Some(Command::Status), Some(config)) => track::status(&config),
+        (Some(Command::Cancel), Some(_)) => track::cancel(),
+        (Some(Command::Undo), Some(config)) => {
+            let client = redmine::request::Client::new(config);
+            track::undo(&client)
+        }
+        (Some(Command::Edit { id }), Some(config)) => {
+            let client = redmine::request::Client::new(config);
+            track::edit(&client, id)
         }
-        (Some(Command::Search { query , direct_track}), Some(config)) => {
+        (Some(Command::Delete { id }), Some(config)) => {
             let client = redmine::request::Client::new(config);
-            track::search(&client, query, direct_track)
+            track::delete(&client, id)
         }
     }
 }
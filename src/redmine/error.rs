@@ -0,0 +1,83 @@
+use reqwest::blocking::Response;
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::redmine::ErrorResponse;
+
+/// An [ApiError] represents an unsuccessful response of the Redmine API.
+#[derive(Error, Debug, PartialEq)]
+pub enum ApiError {
+    /// The values sent have been rejected, with a message per problem.
+    #[error("Redmine rejected the values:\n  {}", .0.join("\n  "))]
+    Validation(Vec<String>),
+    #[error("Redmine did not accept your credentials, please use `track login` again.")]
+    Unauthorized,
+    #[error("You are not allowed to do this in Redmine.")]
+    Forbidden,
+    #[error("Redmine could not find what you were looking for.")]
+    NotFound,
+    #[error("Redmine failed with {0}, please try again later.")]
+    Server(StatusCode),
+    #[error("Redmine responded with an unexpected {0}.")]
+    Unexpected(StatusCode),
+}
+
+impl ApiError {
+    /// Returns the [ApiError] for the `status` and `body` of a response.
+    ///
+    /// Validation errors are read from a body like `{"errors": [...]}`.
+    pub fn new(status: StatusCode, body: &str) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => ApiError::Unauthorized,
+            StatusCode::FORBIDDEN => ApiError::Forbidden,
+            StatusCode::NOT_FOUND => ApiError::NotFound,
+            StatusCode::UNPROCESSABLE_ENTITY => match serde_json::from_str::<ErrorResponse>(body) {
+                Ok(response) => ApiError::Validation(response.errors),
+                Err(_) => ApiError::Unexpected(status),
+            },
+            status if status.is_server_error() => ApiError::Server(status),
+            status => ApiError::Unexpected(status),
+        }
+    }
+
+    /// Returns the [ApiError] of an unsuccessful `response`.
+    pub fn from_response(response: Response) -> Self {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        ApiError::new(status, &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_errors_are_parsed() {
+        let body = r#"{"errors":["Hours cannot be blank","Billable cannot be blank"]}"#;
+        assert_eq!(
+            ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, body),
+            ApiError::Validation(vec![
+                "Hours cannot be blank".to_string(),
+                "Billable cannot be blank".to_string()
+            ])
+        );
+        assert_eq!(
+            ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "<html>"),
+            ApiError::Unexpected(StatusCode::UNPROCESSABLE_ENTITY)
+        );
+    }
+
+    #[test]
+    fn test_status_codes() {
+        assert_eq!(
+            ApiError::new(StatusCode::UNAUTHORIZED, ""),
+            ApiError::Unauthorized
+        );
+        assert_eq!(ApiError::new(StatusCode::NOT_FOUND, ""), ApiError::NotFound);
+        assert_eq!(
+            ApiError::new(StatusCode::BAD_GATEWAY, ""),
+            ApiError::Server(StatusCode::BAD_GATEWAY)
+        );
+    }
+}
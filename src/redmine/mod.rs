@@ -1,7 +1,10 @@
+pub mod error;
 pub mod request;
 
 use serde::{Deserialize, Serialize};
 
+use crate::track::duration::Minutes;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TimeEntries {
     pub time_entries: Vec<TimeEntry>,
@@ -10,6 +13,47 @@ pub struct TimeEntries {
     pub total_count: i32,
 }
 
+/// A [Page] is a single response of a paginated Redmine collection.
+///
+/// Redmine never returns more than `limit` items at once, so the
+/// [crate::redmine::request::Client] uses this to fetch and concatenate
+/// all pages of a collection.
+pub trait Page {
+    /// The total number of items in the collection, across all pages.
+    fn total_count(&self) -> i32;
+
+    /// The number of items in this page.
+    fn item_count(&self) -> usize;
+
+    /// Appends the items of the `next` page to this one.
+    fn append(&mut self, next: Self);
+}
+
+macro_rules! impl_page {
+    ($collection:ty, $items:ident) => {
+        impl Page for $collection {
+            fn total_count(&self) -> i32 {
+                self.total_count
+            }
+
+            fn item_count(&self) -> usize {
+                self.$items.len()
+            }
+
+            fn append(&mut self, next: Self) {
+                self.$items.extend(next.$items);
+                self.limit = self.$items.len() as i32;
+                self.total_count = next.total_count;
+            }
+        }
+    };
+}
+
+impl_page!(TimeEntries, time_entries);
+impl_page!(Projects, projects);
+impl_page!(Issues, issues);
+impl_page!(Results, results);
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Named {
     pub id: i32,
@@ -22,13 +66,20 @@ pub struct TimeEntry {
     pub user: Named,
     pub project: Named,
     pub issue: Option<Named>,
-    pub hours: f64,
+    #[serde(default)]
+    pub activity: Option<Named>,
+    pub hours: Minutes,
     pub comments: Option<String>,
     pub spent_on: String,
     #[serde(default)]
     pub custom_fields: Vec<CustomValue>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimeEntryResponse {
+    pub time_entry: TimeEntry,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NewTimeEntries {
     pub time_entry: NewTimeEntry,
@@ -37,9 +88,10 @@ pub struct NewTimeEntries {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NewTimeEntry {
     pub issue_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub project_id: Option<i32>,
     pub spent_on: String,
-    pub hours: f64,
+    pub hours: Minutes,
     pub activity_id: i32,
     pub comments: String,
     pub custom_fields: Vec<CustomValue>,
@@ -59,6 +111,27 @@ pub struct Project {
     pub name: String,
     pub identifier: String,
     pub parent: Option<Named>,
+    /// The status, which is [PROJECT_ACTIVE] for projects, that accept
+    /// time entries.
+    #[serde(default)]
+    pub status: Option<i32>,
+    /// The activities enabled for this project, only returned by
+    /// [crate::redmine::request::Client::get_project].
+    #[serde(default)]
+    pub time_entry_activities: Vec<Activity>,
+    /// The custom fields of time entries enabled for this project, only
+    /// returned by [crate::redmine::request::Client::get_project] and
+    /// only by Redmine servers, that support including them.
+    #[serde(default)]
+    pub time_entry_custom_fields: Vec<Named>,
+}
+
+/// The status of a [Project], that is neither closed nor archived.
+pub const PROJECT_ACTIVE: i32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProjectResponse {
+    pub project: Project,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -84,11 +157,36 @@ pub struct Result {
     pub url: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssueResponse {
+    pub issue: Issue,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Issue {
     pub id: i32,
     pub subject: String,
     pub project: Named,
+    #[serde(default)]
+    pub status: Option<IssueStatus>,
+    #[serde(default)]
+    pub assigned_to: Option<Named>,
+    #[serde(default)]
+    pub updated_on: Option<String>,
+}
+
+/// The status of an [Issue]. Redmine tells whether it is closed since
+/// version 5.1 only.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssueStatus {
+    pub id: i32,
+    pub name: String,
+    pub is_closed: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssueStatuses {
+    pub issue_statuses: Vec<IssueStatus>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -116,6 +214,14 @@ pub struct CustomField {
     pub is_required: Option<bool>,
     pub field_format: String,
     pub customized_type: String,
+    /// Whether more than one value can be chosen.
+    #[serde(default)]
+    pub multiple: Option<bool>,
+    #[serde(default)]
+    pub default_value: Option<String>,
+    /// The values of list, key/value, user and version fields.
+    #[serde(default)]
+    pub possible_values: Vec<PossibleValue>,
 }
 
 impl CustomField {
@@ -126,13 +232,67 @@ impl CustomField {
     pub fn is_required(&self) -> bool {
         self.is_required.unwrap_or(false)
     }
+
+    pub fn is_multiple(&self) -> bool {
+        self.multiple.unwrap_or(false)
+    }
+
+    /// Returns the default value, if it is not empty.
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref().filter(|v| !v.is_empty())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PossibleValue {
+    pub value: String,
+    pub label: Option<String>,
+}
+
+impl PossibleValue {
+    /// Returns the label or the value, if there is no label.
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.value)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct CustomValue {
     pub id: i32,
     pub name: String,
-    pub value: Option<String>,
+    pub value: Option<FieldValue>,
+}
+
+/// The value of a [CustomValue], which is a list for fields with
+/// multiple values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl FieldValue {
+    /// Returns all values, a single value being a list of one.
+    pub fn values(&self) -> Vec<&str> {
+        match self {
+            FieldValue::Single(value) => vec![value.as_str()],
+            FieldValue::Multiple(values) => values.iter().map(|v| v.as_str()).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.values().join(","))
+    }
+}
+
+/// The body of a response, that Redmine rejected, e.g. with
+/// `422 Unprocessable Entity`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorResponse {
+    pub errors: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1,18 +1,31 @@
-use anyhow::anyhow;
-use chrono::{DateTime, Local};
+use std::cell::OnceCell;
+
+use chrono::NaiveDate;
 use reqwest::blocking;
 use url::Url;
 
+use crate::redmine::error::ApiError;
 use crate::redmine::{
-    Activities, CustomFields, Issues, NewTimeEntries, NewTimeEntry, Projects, Results, TimeEntries,
-    User, UserResponse,
+    Activities, CustomFields, Issue, IssueResponse, IssueStatuses, Issues, NewTimeEntries,
+    NewTimeEntry, Page, Project, ProjectResponse, Projects, Results, TimeEntries, TimeEntry,
+    TimeEntryResponse, User, UserResponse,
 };
 use crate::track::Config;
 
+/// The maximum number of items Redmine returns per page.
+const PAGE_LIMIT: usize = 100;
+
+/// The number of issue ids sent in a single `issue_id` filter, to keep
+/// the request URL reasonably short.
+const ISSUE_BATCH_SIZE: usize = 50;
+
 #[derive(Debug)]
 pub struct Client {
     client: blocking::Client,
     config: Config,
+    /// The latest time entries of the user, which are needed by several
+    /// prompts and fetched only once.
+    recent_time_entries: OnceCell<TimeEntries>,
 }
 
 impl Client {
@@ -20,17 +33,22 @@ impl Client {
         Client {
             client: blocking::Client::new(),
             config,
+            recent_time_entries: OnceCell::new(),
         }
     }
 
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     pub fn get_time_entries(
         &self,
-        start: DateTime<Local>,
-        end: Option<DateTime<Local>>,
+        start: NaiveDate,
+        end: Option<NaiveDate>,
     ) -> anyhow::Result<TimeEntries> {
         let date = start.format("%Y-%m-%d").to_string();
         let user_id = self.config.user_id;
-        let mut query = vec![("user_id", user_id.to_string()), ("limit", 100.to_string())];
+        let mut query = vec![("user_id", user_id.to_string())];
         match end {
             None => {
                 query.push(("spent_on", date));
@@ -42,51 +60,189 @@ impl Client {
             }
         };
 
-        self.get("time_entries.json", query)
+        self.get_all("time_entries.json", query)
+    }
+
+    /// Returns the latest time entries of the user, regardless of when
+    /// they have been spent.
+    ///
+    /// They are fetched once and reused by later calls.
+    pub fn get_recent_time_entries(&self) -> anyhow::Result<TimeEntries> {
+        if let Some(entries) = self.recent_time_entries.get() {
+            return Ok(entries.clone());
+        }
+
+        let query = vec![
+            ("user_id", self.config.user_id.to_string()),
+            ("limit", PAGE_LIMIT.to_string()),
+        ];
+
+        let entries: TimeEntries = self.get("time_entries.json", query)?;
+        Ok(self.recent_time_entries.get_or_init(|| entries).clone())
+    }
+
+    pub fn get_issue(&self, id: i32) -> anyhow::Result<Issue> {
+        let response: IssueResponse = self.get(&format!("issues/{}.json", id), vec![])?;
+        Ok(response.issue)
+    }
+
+    /// Returns all issues with the given ids, regardless of their status.
+    ///
+    /// The ids are requested in batches of [ISSUE_BATCH_SIZE].
+    pub fn get_issues(&self, issue_ids: &[String]) -> anyhow::Result<Issues> {
+        let mut issues = Issues {
+            issues: vec![],
+            offset: 0,
+            limit: 0,
+            total_count: 0,
+        };
+
+        for batch in issue_ids.chunks(ISSUE_BATCH_SIZE) {
+            let query = vec![
+                ("issue_id", batch.join(",")),
+                ("status_id", String::from("*")),
+            ];
+            issues.append(self.get_all("issues.json", query)?);
+        }
+
+        issues.total_count = issues.issues.len() as i32;
+        Ok(issues)
+    }
+
+    /// Returns the latest updated open issues assigned to the user.
+    pub fn get_assigned_issues(&self) -> anyhow::Result<Issues> {
+        self.get_latest_issues(("assigned_to_id", "me"))
+    }
+
+    /// Returns the latest updated open issues watched by the user.
+    pub fn get_watched_issues(&self) -> anyhow::Result<Issues> {
+        self.get_latest_issues(("watcher_id", "me"))
     }
 
-    pub fn get_issues(&self, issue_ids: &Vec<String>) -> anyhow::Result<Issues> {
-        let issue_ids = issue_ids.join(",");
+    /// Returns a single page of the latest updated open issues matching
+    /// the given `filter`.
+    fn get_latest_issues(&self, filter: (&str, &str)) -> anyhow::Result<Issues> {
         let query = vec![
-            ("issue_id", issue_ids),
-            ("status_id", String::from("*")),
-            ("limit", 100.to_string()),
+            (filter.0, filter.1.to_string()),
+            ("status_id", String::from("open")),
+            ("sort", String::from("updated_on:desc")),
+            ("limit", PAGE_LIMIT.to_string()),
         ];
 
         self.get("issues.json", query)
     }
-    pub fn search_tickets(&self, query: String) -> anyhow::Result<Results> {
+
+    /// Returns all issues visible to the user, that have been updated
+    /// `since` the given time like `2024-03-18T10:00:00Z` or day like
+    /// `2024-03-18`, oldest first. Only open ones, if `open_only` is set.
+    pub fn get_updated_issues(&self, since: &str, open_only: bool) -> anyhow::Result<Issues> {
+        let status = if open_only { "open" } else { "*" };
         let query = vec![
+            ("status_id", String::from(status)),
+            ("updated_on", format!(">={}", since)),
+            ("sort", String::from("updated_on:asc")),
+        ];
+
+        self.get_all("issues.json", query)
+    }
+
+    /// Searches for issues with the `query` in their title, within the
+    /// given `project` and its subprojects, if any.
+    pub fn search_tickets(
+        &self,
+        query: String,
+        project: Option<&str>,
+        open_only: bool,
+    ) -> anyhow::Result<Results> {
+        let mut query = vec![
             ("q", [" ", &*query.to_string(), " "].join("")),
-            ("limit", 100.to_string()),
             ("issues", 1.to_string()),
             ("titles_only", 1.to_string()),
         ];
+        if open_only {
+            query.push(("open_issues", 1.to_string()));
+        }
 
-        self.get("search.json", query)
+        match project {
+            Some(project) => {
+                query.push(("scope", String::from("subprojects")));
+                self.get_all(&format!("projects/{}/search.json", project), query)
+            }
+            None => self.get_all("search.json", query),
+        }
     }
 
     pub fn get_projects(&self) -> anyhow::Result<Projects> {
         let user_id = self.config.user_id;
-        let query = vec![("user_id", user_id.to_string()), ("limit", 100.to_string())];
+        let query = vec![("user_id", user_id.to_string())];
+
+        self.get_all("projects.json", query)
+    }
 
-        self.get("projects.json", query)
+    /// Returns the project with the given `id`, including the activities
+    /// and the custom fields of time entries enabled for it.
+    pub fn get_project(&self, id: i32) -> anyhow::Result<Project> {
+        let query = vec![(
+            "include",
+            "time_entry_activities,time_entry_custom_fields".to_string(),
+        )];
+        let response: ProjectResponse = self.get(&format!("projects/{}.json", id), query)?;
+        Ok(response.project)
+    }
+
+    pub fn get_issue_statuses(&self) -> anyhow::Result<IssueStatuses> {
+        self.get("issue_statuses.json", vec![])
     }
 
     pub fn get_activities(&self) -> anyhow::Result<Activities> {
         self.get("enumerations/time_entry_activities.json", vec![])
     }
 
+    /// Returns the custom fields of the [Config], if there are any or if
+    /// they have been discovered by `track fields sync`, and the ones
+    /// defined in Redmine otherwise.
     pub fn get_custom_fields(&self) -> anyhow::Result<CustomFields> {
-        if !&self.config.custom_fields.is_empty() {
+        if self.config.custom_fields_synced || !self.config.custom_fields.is_empty() {
             return Ok(CustomFields {
                 custom_fields: self.config.custom_fields.clone(),
             });
         }
+        self.get_defined_custom_fields()
+    }
+
+    /// Returns all custom fields defined in Redmine, which only
+    /// administrators are allowed to see.
+    pub fn get_defined_custom_fields(&self) -> anyhow::Result<CustomFields> {
         self.get("custom_fields.json", vec![])
     }
 
-    pub fn create_time_entry(&self, entry: NewTimeEntry) -> anyhow::Result<()> {
+    /// Returns the validation errors of an empty time entry.
+    ///
+    /// Redmine rejects it with a message per missing value, including
+    /// the required custom fields, so nothing is ever created.
+    pub fn get_time_entry_errors(&self) -> anyhow::Result<Vec<String>> {
+        let key = &self.config.key;
+        let url = &self.config.base_url.join("time_entries.json")?;
+
+        let response = self
+            .client
+            .post(url.clone())
+            .json(&serde_json::json!({ "time_entry": {} }))
+            .header("X-Redmine-API-Key", key)
+            .send()?;
+
+        match ApiError::from_response(response) {
+            ApiError::Validation(errors) => Ok(errors),
+            err => Err(err.into()),
+        }
+    }
+
+    pub fn get_time_entry(&self, id: i32) -> anyhow::Result<TimeEntry> {
+        let response: TimeEntryResponse = self.get(&format!("time_entries/{}.json", id), vec![])?;
+        Ok(response.time_entry)
+    }
+
+    pub fn create_time_entry(&self, entry: NewTimeEntry) -> anyhow::Result<TimeEntry> {
         let key = &self.config.key;
         let url = &self.config.base_url.join("time_entries.json")?;
         let new_entry = NewTimeEntries { time_entry: entry };
@@ -98,26 +254,88 @@ impl Client {
             .header("X-Redmine-API-Key", key)
             .send()?;
 
+        if response.status().is_success() {
+            let created: TimeEntryResponse = response.json()?;
+            return Ok(created.time_entry);
+        }
+
+        Err(ApiError::from_response(response).into())
+    }
+
+    pub fn update_time_entry(&self, id: i32, entry: NewTimeEntry) -> anyhow::Result<()> {
+        let key = &self.config.key;
+        let url = &self
+            .config
+            .base_url
+            .join(&format!("time_entries/{}.json", id))?;
+        let entry = NewTimeEntries { time_entry: entry };
+
+        let response = self
+            .client
+            .put(url.clone())
+            .json(&entry)
+            .header("X-Redmine-API-Key", key)
+            .send()?;
+
         if response.status().is_success() {
             return Ok(());
         }
 
-        let status = &response.status();
-        let headers = response
-            .headers()
-            .iter()
-            .map(|(name, value)| format!("{:?}: {:?}", name, value))
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        match &response.text() {
-            Ok(value) => println!("{}", value),
-            Err(err) => eprintln!("{:?}", err),
+        Err(ApiError::from_response(response).into())
+    }
+
+    pub fn delete_time_entry(&self, id: i32) -> anyhow::Result<()> {
+        let key = &self.config.key;
+        let url = &self
+            .config
+            .base_url
+            .join(&format!("time_entries/{}.json", id))?;
+
+        let response = self
+            .client
+            .delete(url.clone())
+            .header("X-Redmine-API-Key", key)
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(());
         }
 
-        let msg = format!("{}\n\n{}", status, headers);
+        Err(ApiError::from_response(response).into())
+    }
+
+    /// Fetches every page of the collection at `path` and returns them
+    /// concatenated into a single [Page].
+    fn get_all<T>(&self, path: &str, query: Vec<(&str, String)>) -> anyhow::Result<T>
+    where
+        T: serde::de::DeserializeOwned + Page,
+    {
+        let mut offset = 0;
+        let mut result: Option<T> = None;
+        loop {
+            let mut page_query = query.clone();
+            page_query.push(("offset", offset.to_string()));
+            page_query.push(("limit", PAGE_LIMIT.to_string()));
+
+            let page: T = self.get(path, page_query)?;
+            let fetched = page.item_count();
+            let total_count = page.total_count().max(0) as usize;
+
+            let all = match result {
+                None => page,
+                Some(mut all) => {
+                    all.append(page);
+                    all
+                }
+            };
+
+            offset += fetched;
+            if fetched == 0 || offset >= total_count {
+                return Ok(all);
+            }
 
-        Err(anyhow!(msg))
+            result = Some(all);
+        }
     }
 
     fn get<T>(&self, path: &str, query: Vec<(&str, String)>) -> anyhow::Result<T>
@@ -127,15 +345,18 @@ impl Client {
         let key = self.config.key.clone();
         let url = self.config.base_url.clone().join(path)?;
 
-        let result = self
+        let response = self
             .client
             .get(url)
             .header("X-Redmine-API-Key", key)
             .query(&query)
-            .send()?
-            .json()?;
+            .send()?;
+
+        if !response.status().is_success() {
+            return Err(ApiError::from_response(response).into());
+        }
 
-        Ok(result)
+        Ok(response.json()?)
     }
 }
 
@@ -146,11 +367,12 @@ pub fn login(
     password: String,
 ) -> anyhow::Result<User> {
     let url = base_url.join("users/current.json")?;
-    let result: UserResponse = client
-        .get(url)
-        .basic_auth(user, Some(password))
-        .send()?
-        .json()?;
+    let response = client.get(url).basic_auth(user, Some(password)).send()?;
+
+    if !response.status().is_success() {
+        return Err(ApiError::from_response(response).into());
+    }
 
+    let result: UserResponse = response.json()?;
     Ok(result.user)
 }
@@ -0,0 +1,161 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::redmine::Issue;
+use crate::track::Error;
+
+/// The number of days, in which issues have to be updated, to be fetched
+/// by the first refresh of the [IssueCache].
+pub const FIRST_SYNC_DAYS: u64 = 365;
+
+/// An [IssueCache] keeps the issues visible to the user, so they can be
+/// searched without asking Redmine, most recently updated first.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct IssueCache {
+    /// The latest `updated_on` of all cached issues, from which the next
+    /// refresh continues.
+    #[serde(default)]
+    pub updated_on: Option<String>,
+    #[serde(default)]
+    pub issues: Vec<Issue>,
+}
+
+impl IssueCache {
+    /// Load the cache from `~/.track_issues`.
+    ///
+    /// Returns an empty [IssueCache], if the file does not exist yet.
+    pub fn load() -> Result<Self, Error> {
+        Ok(super::load_json(".track_issues")?.unwrap_or_default())
+    }
+
+    /// Store this cache in `~/.track_issues`.
+    pub fn store(&self) -> Result<(), Error> {
+        super::store_json(".track_issues", self)
+    }
+
+    /// Adds the `updated` issues, replacing the cached ones with the same
+    /// id, and remembers the latest `updated_on`. Of issues contained
+    /// more than once, the last one is kept.
+    pub fn merge(&mut self, updated: Vec<Issue>) {
+        let mut ids = HashSet::new();
+        let updated: Vec<Issue> = updated
+            .into_iter()
+            .rev()
+            .filter(|issue| ids.insert(issue.id))
+            .collect();
+        self.issues.retain(|cached| !ids.contains(&cached.id));
+        self.issues.extend(updated);
+
+        self.issues
+            .sort_by(|a, b| b.updated_on.cmp(&a.updated_on).then(b.id.cmp(&a.id)));
+        self.updated_on = self
+            .issues
+            .iter()
+            .filter_map(|i| i.updated_on.clone())
+            .max();
+    }
+
+    /// Returns the issues matching the `query`, best matches first.
+    ///
+    /// Every word of the `query` has to be found in the id, subject or
+    /// project of an issue, either as a whole or with its letters in
+    /// order, like `fxlgn` for `fix login`.
+    pub fn search(&self, query: &str) -> Vec<&Issue> {
+        let mut matches: Vec<(i64, &Issue)> = self
+            .issues
+            .iter()
+            .filter_map(|issue| {
+                let text = format!(
+                    "#{} {} {}",
+                    issue.id,
+                    issue.subject,
+                    issue.project.name.as_deref().unwrap_or_default()
+                );
+                score(query, &text).map(|score| (score, issue))
+            })
+            .collect();
+
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+        matches.into_iter().map(|(_, issue)| issue).collect()
+    }
+}
+
+/// Scores how well the `text` matches the words of the `query`, ignoring
+/// the case, or returns [None] if one of the words is not found.
+///
+/// Words found as a whole score higher than scattered ones, even more so
+/// at the start of a word of the `text`.
+fn score(query: &str, text: &str) -> Option<i64> {
+    let text = text.to_lowercase();
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .map(|word| match text.find(word) {
+            Some(0) => Some(4),
+            Some(position) if !text[..position].ends_with(char::is_alphanumeric) => Some(4),
+            Some(_) => Some(3),
+            None if is_subsequence(word, &text) => Some(1),
+            None => None,
+        })
+        .sum()
+}
+
+/// Returns whether all characters of `word` appear in `text` in order.
+fn is_subsequence(word: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+    word.chars().all(|c| chars.any(|t| t == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redmine::Named;
+
+    fn issue(id: i32, subject: &str, updated_on: &str) -> Issue {
+        Issue {
+            id,
+            subject: subject.to_string(),
+            project: Named {
+                id: 1,
+                name: Some("Website".to_string()),
+            },
+            status: None,
+            assigned_to: None,
+            updated_on: Some(updated_on.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_merge_replaces_updated_issues() {
+        let mut cache = IssueCache::default();
+        cache.merge(vec![
+            issue(1, "Login page", "2024-03-18T10:00:00Z"),
+            issue(2, "Logout", "2024-03-19T10:00:00Z"),
+        ]);
+        cache.merge(vec![issue(1, "New login page", "2024-03-20T10:00:00Z")]);
+
+        let subjects: Vec<&str> = cache.issues.iter().map(|i| &*i.subject).collect();
+        assert_eq!(subjects, vec!["New login page", "Logout"]);
+        assert_eq!(cache.updated_on.as_deref(), Some("2024-03-20T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_search_is_fuzzy() {
+        let mut cache = IssueCache::default();
+        cache.merge(vec![
+            issue(1, "Fix the login page", "2024-03-18T10:00:00Z"),
+            issue(2, "Slogan on landing page", "2024-03-19T10:00:00Z"),
+            issue(3, "Imprint", "2024-03-20T10:00:00Z"),
+        ]);
+
+        let ids = |query| -> Vec<i32> { cache.search(query).iter().map(|i| i.id).collect() };
+        assert_eq!(ids("login"), vec![1, 2]);
+        assert_eq!(ids("log page"), vec![1, 2]);
+        assert_eq!(ids("fxlgn"), vec![1]);
+        assert_eq!(ids("#3"), vec![3]);
+        assert_eq!(ids("website imprint"), vec![3]);
+        assert!(ids("invoice").is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+use chrono::{NaiveTime, Timelike};
+use regex::Regex;
+
+use crate::track::duration::Minutes;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// The durations in minutes, that ranges without a colon like `0930-1100`
+/// have to last, to not be mistaken for other numbers.
+const PLAUSIBLE: std::ops::RangeInclusive<i64> = 5..=16 * 60;
+
+/// A [TimeRange] like `09:00 - 10:30` written in a comment.
+///
+/// A range ending before it starts, like `22:00 - 01:00`, is crossing
+/// midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+}
+
+impl TimeRange {
+    /// Returns the duration of this range.
+    pub fn minutes(&self) -> Minutes {
+        let minutes = (self.to - self.from).num_minutes();
+        if minutes < 0 {
+            Minutes::new(minutes + MINUTES_PER_DAY)
+        } else {
+            Minutes::new(minutes)
+        }
+    }
+
+    /// Returns the start and end of this range in minutes after the
+    /// midnight it starts from.
+    fn bounds(&self) -> (i64, i64) {
+        let start = (self.from.hour() * 60 + self.from.minute()) as i64;
+        (start, start + self.minutes().minutes())
+    }
+}
+
+impl std::fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} - {}",
+            self.from.format("%H:%M"),
+            self.to.format("%H:%M")
+        )
+    }
+}
+
+/// Finds all time ranges in the given `comment`.
+///
+/// Times can be written as `9:30`, `09:30`, `9.30` or `0930`, multiple
+/// ranges may be separated by anything, e.g. `09:00-10:30, 13:00-14:15`.
+/// Ranges being part of something longer like a date `10.03.2024-12.03`
+/// are ignored, just like ranges without a colon, that do not last a
+/// [PLAUSIBLE] time, so numbers like `2023-2024` or versions like `1.10 -
+/// 1.12` are no ranges. Overlapping ranges are reported as an error.
+pub fn time_ranges(comment: &str) -> Result<Vec<TimeRange>, String> {
+    let re = Regex::new(r"\b(\d{1,2}[:.]\d{2}|\d{4})\s*-\s*(\d{1,2}[:.]\d{2}|\d{4})\b")
+        .expect("The time range pattern should be valid.");
+    let is_separate = |range: regex::Match| {
+        let before = comment[..range.start()].chars().next_back();
+        let after = comment[range.end()..].chars().next();
+        ![before, after]
+            .iter()
+            .any(|c| matches!(c, Some('.' | ':' | '-')))
+    };
+
+    let ranges: Vec<TimeRange> = re
+        .captures_iter(comment)
+        .filter(|captures| is_separate(captures.get(0).expect("There should be a match.")))
+        .filter_map(|captures| {
+            let from = parse_time(&captures[1])?;
+            let to = parse_time(&captures[2])?;
+            let range = TimeRange { from, to };
+            let has_colon = captures[1].contains(':') || captures[2].contains(':');
+            (has_colon || PLAUSIBLE.contains(&range.minutes().minutes())).then_some(range)
+        })
+        .collect();
+
+    let mut sorted: Vec<(TimeRange, (i64, i64))> =
+        ranges.iter().copied().zip(bounds(&ranges)).collect();
+    sorted.sort_by_key(|(_, bounds)| *bounds);
+    for pair in sorted.windows(2) {
+        let [(first, (_, end)), (second, (start, _))] = pair else {
+            continue;
+        };
+        if start < end {
+            return Err(format!("The time ranges {} and {} overlap.", first, second));
+        }
+    }
+
+    Ok(ranges)
+}
+
+/// Returns the start and end of the `ranges` in minutes after the
+/// midnight the first one starts from.
+///
+/// A range following one, that crosses midnight, is placed on the next
+/// day, if it starts earlier than that one.
+fn bounds(ranges: &[TimeRange]) -> Vec<(i64, i64)> {
+    let mut crossed_at = None;
+    ranges
+        .iter()
+        .map(|range| {
+            let (start, end) = range.bounds();
+            if end > MINUTES_PER_DAY {
+                crossed_at = Some(start);
+            }
+            match crossed_at {
+                Some(crossed_at) if start < crossed_at => {
+                    (start + MINUTES_PER_DAY, end + MINUTES_PER_DAY)
+                }
+                _ => (start, end),
+            }
+        })
+        .collect()
+}
+
+/// Returns the sum of all time ranges in the given `comment` or [None],
+/// if there are none.
+pub fn duration(comment: &str) -> Result<Option<Minutes>, String> {
+    let ranges = time_ranges(comment)?;
+    if ranges.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ranges.iter().map(|range| range.minutes()).sum()))
+}
+
+fn parse_time(input: &str) -> Option<NaiveTime> {
+    let (hours, minutes) = match input.split_once([':', '.']) {
+        Some(parts) => parts,
+        None => input.split_at(2),
+    };
+
+    NaiveTime::from_hms_opt(hours.parse().ok()?, minutes.parse().ok()?, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_range() {
+        assert_eq!(duration("14:00 - 14:50 Review"), Ok(Some(Minutes::new(50))));
+        assert_eq!(duration("Review"), Ok(None));
+    }
+
+    #[test]
+    fn test_multiple_ranges_are_summed() {
+        assert_eq!(
+            duration("09:00-10:30, 13:00-14:15 refactoring"),
+            Ok(Some(Minutes::new(165)))
+        );
+    }
+
+    #[test]
+    fn test_alternative_time_formats() {
+        assert_eq!(duration("9.30 - 10:00"), Ok(Some(Minutes::new(30))));
+        assert_eq!(duration("0930-11:00"), Ok(Some(Minutes::new(90))));
+        assert_eq!(duration("0930-1100"), Ok(Some(Minutes::new(90))));
+        assert_eq!(duration("9.30-10.30"), Ok(Some(Minutes::new(60))));
+        assert_eq!(duration("9:30-25:00"), Ok(None));
+    }
+
+    #[test]
+    fn test_numbers_are_no_ranges() {
+        assert_eq!(duration("Budget 2023-2024 planning"), Ok(None));
+        assert_eq!(duration("release 1.10 - 1.12"), Ok(None));
+        assert_eq!(duration("from 10.03.2024-12.03.2024"), Ok(None));
+    }
+
+    #[test]
+    fn test_range_crossing_midnight() {
+        assert_eq!(duration("22:00 - 01:00"), Ok(Some(Minutes::new(180))));
+    }
+
+    #[test]
+    fn test_overlapping_ranges_are_an_error() {
+        assert!(duration("09:00-10:30, 10:00-11:00").is_err());
+        assert!(duration("22:00-02:00, 23:00-23:30").is_err());
+        assert!(duration("22:00-02:00, 01:00-01:30").is_err());
+        assert_eq!(
+            duration("22:00-02:00, 02:00-03:00"),
+            Ok(Some(Minutes::new(300)))
+        );
+        assert_eq!(
+            duration("09:00-10:00, 10:00-11:00"),
+            Ok(Some(Minutes::new(120)))
+        );
+    }
+}
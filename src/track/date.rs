@@ -0,0 +1,92 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+/// Parses a date relative to `today`.
+///
+/// The following forms are supported:
+///
+/// - `today` and `yesterday`
+/// - ISO dates like `2024-03-18`
+/// - days relative to today like `-3` or `+1`
+/// - weekdays like `monday` or `mon`, meaning the most recent one, which
+///   might be today
+/// - `last` followed by a weekday like `last friday`, meaning the day in
+///   the week before the current one
+///
+/// ## Example
+///
+/// ```
+/// let today = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+/// let monday = NaiveDate::from_ymd_opt(2024, 3, 18).unwrap();
+/// assert_eq!(parse("monday", today), Ok(monday));
+/// ```
+pub fn parse(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let input = input.trim().to_lowercase();
+    let invalid = || format!("The date {} could not be understood.", input);
+
+    match input.as_str() {
+        "today" => return Ok(today),
+        "yesterday" => return today.checked_sub_days(Days::new(1)).ok_or_else(invalid),
+        _ => {}
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    if let Some(days) = input.strip_prefix('-') {
+        let days = days.parse::<u64>().map_err(|_| invalid())?;
+        return today.checked_sub_days(Days::new(days)).ok_or_else(invalid);
+    }
+
+    if let Some(days) = input.strip_prefix('+') {
+        let days = days.parse::<u64>().map_err(|_| invalid())?;
+        return today.checked_add_days(Days::new(days)).ok_or_else(invalid);
+    }
+
+    if let Some(weekday) = input.strip_prefix("last ") {
+        let weekday = weekday.trim().parse::<Weekday>().map_err(|_| invalid())?;
+        let days_back = today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday();
+        return today
+            .checked_sub_days(Days::new(days_back as u64))
+            .ok_or_else(invalid);
+    }
+
+    let weekday = input.parse::<Weekday>().map_err(|_| invalid())?;
+    let days_back =
+        (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+    today
+        .checked_sub_days(Days::new(days_back as u64))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_absolute_and_relative_days() {
+        let wednesday = date(2024, 3, 20);
+        assert_eq!(parse("today", wednesday), Ok(wednesday));
+        assert_eq!(parse("yesterday", wednesday), Ok(date(2024, 3, 19)));
+        assert_eq!(parse("2024-01-31", wednesday), Ok(date(2024, 1, 31)));
+        assert_eq!(parse("-3", wednesday), Ok(date(2024, 3, 17)));
+        assert_eq!(parse("+1", wednesday), Ok(date(2024, 3, 21)));
+        assert!(parse("-x", wednesday).is_err());
+        assert!(parse("someday", wednesday).is_err());
+    }
+
+    #[test]
+    fn test_parse_weekdays() {
+        let wednesday = date(2024, 3, 20);
+        assert_eq!(parse("wednesday", wednesday), Ok(wednesday));
+        assert_eq!(parse("Monday", wednesday), Ok(date(2024, 3, 18)));
+        assert_eq!(parse("fri", wednesday), Ok(date(2024, 3, 15)));
+        assert_eq!(parse("last monday", wednesday), Ok(date(2024, 3, 11)));
+        assert_eq!(parse("last friday", wednesday), Ok(date(2024, 3, 15)));
+        assert_eq!(parse("last wednesday", wednesday), Ok(date(2024, 3, 13)));
+    }
+}
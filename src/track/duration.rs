@@ -0,0 +1,413 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// [Minutes] represent a duration as an exact number of minutes.
+///
+/// Redmine works with decimal hours, so [Minutes] are (de)serialized as
+/// such, which makes it possible to use them directly in the API types,
+/// while all arithmetic stays exact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Minutes(i64);
+
+/// The [DurationFormat] defines how [Minutes] are displayed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum DurationFormat {
+    /// Decimal hours like `7.75`.
+    #[default]
+    Decimal,
+    /// Hours and minutes like `7:45`.
+    Clock,
+}
+
+/// A [Rounding] policy defines how computed or typed durations are
+/// rounded to the increments, that are billed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct Rounding {
+    pub mode: RoundingMode,
+    /// The increment in minutes, e.g. 6, 10 or 15.
+    #[serde(default = "default_increment")]
+    pub increment: i64,
+}
+
+/// The direction in which a [Rounding] rounds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum RoundingMode {
+    /// Do not round at all.
+    #[default]
+    Exact,
+    /// Round down to the previous increment.
+    Down,
+    /// Round up to the next increment.
+    Up,
+    /// Round to the nearest increment, halves are rounded up.
+    Nearest,
+}
+
+fn default_increment() -> i64 {
+    15
+}
+
+impl Rounding {
+    /// The rounding of durations computed from the time ranges in a
+    /// comment, if no policy is configured, which cuts them down to
+    /// quarter hours.
+    pub const RANGES: Rounding = Rounding {
+        mode: RoundingMode::Down,
+        increment: 15,
+    };
+}
+
+impl Default for Rounding {
+    fn default() -> Self {
+        Rounding {
+            mode: RoundingMode::Exact,
+            increment: default_increment(),
+        }
+    }
+}
+
+impl Minutes {
+    pub const ZERO: Minutes = Minutes(0);
+
+    pub fn new(minutes: i64) -> Self {
+        Minutes(minutes)
+    }
+
+    /// Converts the decimal `hours` into minutes, rounding to the nearest
+    /// minute.
+    pub fn from_hours(hours: f64) -> Self {
+        Minutes((hours * 60.0).round() as i64)
+    }
+
+    /// Returns the sum of these and the `other` minutes or [None], if it
+    /// overflows.
+    pub fn checked_add(self, other: Minutes) -> Option<Minutes> {
+        self.0.checked_add(other.0).map(Minutes)
+    }
+
+    pub fn minutes(&self) -> i64 {
+        self.0
+    }
+
+    /// Returns these minutes as decimal hours, like Redmine expects them.
+    pub fn as_hours(&self) -> f64 {
+        self.0 as f64 / 60.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Rounds these minutes according to the given `rounding` policy.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// let rounding = Rounding { mode: RoundingMode::Up, increment: 15 };
+    /// assert_eq!(Minutes::new(50).round(rounding), Minutes::new(60));
+    /// ```
+    pub fn round(self, rounding: Rounding) -> Minutes {
+        let increment = rounding.increment;
+        if increment <= 1 {
+            return self;
+        }
+
+        let down = self.0.div_euclid(increment) * increment;
+        let remainder = self.0 - down;
+        match rounding.mode {
+            RoundingMode::Exact => self,
+            RoundingMode::Down => Minutes(down),
+            RoundingMode::Up if remainder == 0 => self,
+            RoundingMode::Up => Minutes(down + increment),
+            RoundingMode::Nearest if remainder * 2 >= increment => Minutes(down + increment),
+            RoundingMode::Nearest => Minutes(down),
+        }
+    }
+
+    /// Formats these minutes in the given `format`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// assert_eq!(Minutes::new(465).format(DurationFormat::Decimal), "7.75");
+    /// assert_eq!(Minutes::new(465).format(DurationFormat::Clock), "7:45");
+    /// ```
+    pub fn format(&self, format: DurationFormat) -> String {
+        match format {
+            DurationFormat::Decimal => {
+                let hours = format!("{:.2}", self.as_hours());
+                hours
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+                    .to_string()
+            }
+            DurationFormat::Clock => {
+                let sign = if self.0 < 0 { "-" } else { "" };
+                let minutes = self.0.abs();
+                format!("{}{}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Minutes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(DurationFormat::Decimal))
+    }
+}
+
+impl FromStr for Minutes {
+    type Err = String;
+
+    /// Parses a duration like `1.5`, `1,5`, `1:30`, `1h30m`, `1h` or `90m`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input: String = s.split_whitespace().collect::<String>().to_lowercase();
+        let error = || {
+            format!(
+                "{} is not a valid duration, try e.g. 1.5, 1:30 or 1h30m.",
+                s.trim()
+            )
+        };
+
+        if let Some((hours, minutes)) = input.split_once(':') {
+            let hours = digits(hours).ok_or_else(error)?;
+            let minutes = digits(minutes)
+                .filter(|m| *m < 60 && minutes.len() == 2)
+                .ok_or_else(error)?;
+            return hours
+                .checked_mul(60)
+                .and_then(|hours| hours.checked_add(minutes))
+                .map(Minutes)
+                .ok_or_else(error);
+        }
+
+        if input.ends_with(['h', 'm']) {
+            let (hours, minutes) = input.split_once('h').unwrap_or(("", &input));
+            let hours = match hours {
+                "" => Minutes::ZERO,
+                hours => decimal_hours(hours).ok_or_else(error)?,
+            };
+            let minutes = match minutes.strip_suffix('m') {
+                Some(minutes) => digits(minutes).ok_or_else(error)?,
+                None if minutes.is_empty() => 0,
+                None => return Err(error()),
+            };
+            return hours.checked_add(Minutes(minutes)).ok_or_else(error);
+        }
+
+        decimal_hours(&input).ok_or_else(error)
+    }
+}
+
+/// Parses a non-empty sequence of ASCII digits.
+fn digits(input: &str) -> Option<i64> {
+    if input.is_empty() || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    input.parse().ok()
+}
+
+/// Parses non-negative decimal `hours` with a decimal point or comma.
+fn decimal_hours(hours: &str) -> Option<Minutes> {
+    let hours = hours.replace(',', ".");
+    if !hours.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return None;
+    }
+
+    hours
+        .parse::<f64>()
+        .ok()
+        .filter(|hours| hours * 60.0 < i64::MAX as f64)
+        .map(Minutes::from_hours)
+}
+
+/// The [HoursInput] is a duration typed by the user, either an absolute
+/// one like `1h30m` or an adjustment of another duration like `+15m`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoursInput {
+    Absolute(Minutes),
+    Adjustment(Minutes),
+}
+
+impl HoursInput {
+    /// Returns the resulting duration, applying an adjustment to `base`.
+    pub fn resolve(self, base: Minutes) -> Result<Minutes, String> {
+        let hours = match self {
+            HoursInput::Absolute(hours) => hours,
+            HoursInput::Adjustment(delta) => base
+                .checked_add(delta)
+                .ok_or_else(|| format!("{}h more is too long.", delta))?,
+        };
+
+        if hours < Minutes::ZERO {
+            Err(format!("{}h is less than nothing.", hours))
+        } else {
+            Ok(hours)
+        }
+    }
+}
+
+impl FromStr for HoursInput {
+    type Err = String;
+
+    /// Parses a [Minutes] duration, optionally prefixed with `+` or `-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        if let Some(delta) = input.strip_prefix('+') {
+            Ok(HoursInput::Adjustment(delta.parse()?))
+        } else if let Some(delta) = input.strip_prefix('-') {
+            Ok(HoursInput::Adjustment(Minutes::ZERO - delta.parse()?))
+        } else {
+            Ok(HoursInput::Absolute(input.parse()?))
+        }
+    }
+}
+
+impl Add for Minutes {
+    type Output = Minutes;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Minutes(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Minutes {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0
+    }
+}
+
+impl Sub for Minutes {
+    type Output = Minutes;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Minutes(self.0 - rhs.0)
+    }
+}
+
+impl Sum for Minutes {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Minutes::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Minutes> for Minutes {
+    fn sum<I: Iterator<Item = &'a Minutes>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl Serialize for Minutes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.as_hours())
+    }
+}
+
+impl<'de> Deserialize<'de> for Minutes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Minutes::from_hours)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sum_is_exact() {
+        let hours = [0.1, 0.2, 0.7, 7.0];
+        let total: Minutes = hours.iter().map(|h| Minutes::from_hours(*h)).sum();
+        assert_eq!(total, Minutes::new(480));
+        assert_eq!(total.format(DurationFormat::Decimal), "8");
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Minutes::new(465).format(DurationFormat::Decimal), "7.75");
+        assert_eq!(Minutes::new(20).format(DurationFormat::Decimal), "0.33");
+        assert_eq!(Minutes::new(90).format(DurationFormat::Decimal), "1.5");
+        assert_eq!(Minutes::new(465).format(DurationFormat::Clock), "7:45");
+        assert_eq!(Minutes::new(5).format(DurationFormat::Clock), "0:05");
+    }
+
+    #[test]
+    fn test_round() {
+        let rounding = |mode, increment| Rounding { mode, increment };
+        let fifty = Minutes::new(50);
+        assert_eq!(fifty.round(rounding(RoundingMode::Exact, 15)), fifty);
+        assert_eq!(
+            fifty.round(rounding(RoundingMode::Down, 15)),
+            Minutes::new(45)
+        );
+        assert_eq!(
+            fifty.round(rounding(RoundingMode::Up, 15)),
+            Minutes::new(60)
+        );
+        assert_eq!(
+            fifty.round(rounding(RoundingMode::Nearest, 15)),
+            Minutes::new(45)
+        );
+        assert_eq!(
+            Minutes::new(53).round(rounding(RoundingMode::Nearest, 6)),
+            Minutes::new(54)
+        );
+        assert_eq!(
+            Minutes::new(60).round(rounding(RoundingMode::Up, 10)),
+            Minutes::new(60)
+        );
+        assert_eq!(fifty.round(Rounding::RANGES), Minutes::new(45));
+    }
+
+    #[test]
+    fn test_parse_human_friendly_durations() {
+        let parse = |s: &str| s.parse::<Minutes>();
+        assert_eq!(parse("1.5"), Ok(Minutes::new(90)));
+        assert_eq!(parse("1,5"), Ok(Minutes::new(90)));
+        assert_eq!(parse(".25"), Ok(Minutes::new(15)));
+        assert_eq!(parse("1:30"), Ok(Minutes::new(90)));
+        assert_eq!(parse("0:05"), Ok(Minutes::new(5)));
+        assert_eq!(parse("1h30m"), Ok(Minutes::new(90)));
+        assert_eq!(parse("1h 30m"), Ok(Minutes::new(90)));
+        assert_eq!(parse("2h"), Ok(Minutes::new(120)));
+        assert_eq!(parse("1.5h"), Ok(Minutes::new(90)));
+        assert_eq!(parse("90m"), Ok(Minutes::new(90)));
+        assert!(parse("").is_err());
+        assert!(parse("abc").is_err());
+        assert!(parse("1:75").is_err());
+        assert!(parse("-1").is_err());
+        assert!(parse("1h30").is_err());
+        assert!(parse("NaN").is_err());
+        assert!(parse("1e3").is_err());
+        assert!(parse("999999999999999999:00").is_err());
+        assert!(parse("99999999999999999999h").is_err());
+        assert!(parse("99999999999999999999m").is_err());
+    }
+
+    #[test]
+    fn test_hours_input_adjusts_base() {
+        let base = Minutes::new(60);
+        let resolve = |s: &str| s.parse::<HoursInput>().and_then(|h| h.resolve(base));
+        assert_eq!(resolve("+15m"), Ok(Minutes::new(75)));
+        assert_eq!(resolve("-0:30"), Ok(Minutes::new(30)));
+        assert_eq!(resolve("2"), Ok(Minutes::new(120)));
+        assert!(resolve("-2h").is_err());
+        assert!(resolve("+").is_err());
+        assert!(HoursInput::Adjustment(Minutes::new(i64::MAX))
+            .resolve(base)
+            .is_err());
+    }
+
+    #[test]
+    fn test_serializes_as_decimal_hours() {
+        assert_eq!(serde_json::to_string(&Minutes::new(90)).unwrap(), "1.5");
+        let minutes: Minutes = serde_json::from_str("0.25").unwrap();
+        assert_eq!(minutes, Minutes::new(15));
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::redmine::{CustomField, FieldValue, Named, PossibleValue, TimeEntry};
+use crate::track::date;
+
+/// Parses the `input` for the given custom `field` into the value, that
+/// Redmine expects for its format.
+///
+/// Values of list, key/value, user and version fields can be given by
+/// value or label, multiple values are separated by commas. Dates are
+/// parsed relative to `today` like `--date`. An empty `input` results in
+/// [None], unless the field is required.
+pub fn parse(
+    field: &CustomField,
+    input: &str,
+    today: NaiveDate,
+) -> Result<Option<FieldValue>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return match field.is_required() {
+            true => Err(format!("{} is required.", field.name)),
+            false => Ok(None),
+        };
+    }
+
+    if field.is_multiple() {
+        let values = input
+            .split(',')
+            .map(|value| parse_single(field, value.trim(), today))
+            .collect::<Result<Vec<String>, String>>()?;
+        return Ok(Some(FieldValue::Multiple(values)));
+    }
+
+    parse_single(field, input, today).map(|value| Some(FieldValue::Single(value)))
+}
+
+fn parse_single(field: &CustomField, input: &str, today: NaiveDate) -> Result<String, String> {
+    let invalid = |kind: &str| format!("{} is not a valid {} for {}.", input, kind, field.name);
+
+    if !field.possible_values.is_empty() {
+        return find_possible_value(&field.possible_values, input)
+            .map(|possible| possible.value.clone())
+            .ok_or_else(|| {
+                let labels: Vec<&str> = field.possible_values.iter().map(|v| v.label()).collect();
+                format!(
+                    "{} is not a possible value for {}, try one of {}.",
+                    input,
+                    field.name,
+                    labels.join(", ")
+                )
+            });
+    }
+
+    match &*field.field_format {
+        "bool" => match input.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "y" => Ok("1".to_string()),
+            "0" | "false" | "no" | "n" => Ok("0".to_string()),
+            _ => Err(invalid("boolean")),
+        },
+        "int" => input
+            .parse::<i64>()
+            .map(|value| value.to_string())
+            .map_err(|_| invalid("integer")),
+        "float" => input
+            .replace(',', ".")
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .map(|value| value.to_string())
+            .ok_or_else(|| invalid("number")),
+        "date" => date::parse(input, today).map(|date| date.format("%Y-%m-%d").to_string()),
+        "user" | "version" => input
+            .parse::<u32>()
+            .map(|id| id.to_string())
+            .map_err(|_| invalid("id")),
+        "list" | "enumeration" | "key_value" => Err(format!(
+            "{} has no possible values to choose from.",
+            field.name
+        )),
+        _ => Ok(input.to_string()),
+    }
+}
+
+/// Finds the possible value matching the `input` by value or label,
+/// ignoring the case.
+fn find_possible_value<'a>(
+    possible_values: &'a [PossibleValue],
+    input: &str,
+) -> Option<&'a PossibleValue> {
+    possible_values
+        .iter()
+        .find(|v| v.value == input)
+        .or_else(|| {
+            possible_values
+                .iter()
+                .find(|v| v.label().eq_ignore_ascii_case(input))
+        })
+}
+
+/// The id of a discovered field, that is only known by its name.
+pub const UNKNOWN_ID: i32 = 0;
+
+/// The labels of the values of a time entry, that are not custom fields.
+const ENTRY_LABELS: [&str; 8] = [
+    "Project", "Issue", "User", "Date", "Hours", "Activity", "Comment", "Spent on",
+];
+
+/// Discovers the custom fields of time entries from the values of the
+/// given `entries` and the `project_fields` included in their projects,
+/// for users, that are not allowed to see their definitions.
+///
+/// Fields are marked as required, if one of the validation `errors` of
+/// an empty time entry starts with their name. Errors like `Ticket
+/// cannot be blank` naming an unknown field result in a required field
+/// with the [UNKNOWN_ID]. The format is guessed from the values, which
+/// results in `bool` for fields with only `0` and `1` and in `string`
+/// otherwise.
+pub fn discover(
+    entries: &[TimeEntry],
+    project_fields: &[Named],
+    errors: &[String],
+) -> Vec<CustomField> {
+    let mut values: BTreeMap<i32, (String, Vec<FieldValue>)> = BTreeMap::new();
+    for value in entries.iter().flat_map(|entry| &entry.custom_fields) {
+        let (_, seen) = values
+            .entry(value.id)
+            .or_insert_with(|| (value.name.clone(), vec![]));
+        seen.extend(value.value.clone());
+    }
+    for field in project_fields {
+        if let Some(name) = &field.name {
+            values
+                .entry(field.id)
+                .or_insert_with(|| (name.clone(), vec![]));
+        }
+    }
+
+    let mut fields: Vec<CustomField> = values
+        .into_iter()
+        .map(|(id, (name, seen))| {
+            let multiple = seen.iter().any(|v| matches!(v, FieldValue::Multiple(_)));
+            let plain: Vec<&str> = seen
+                .iter()
+                .flat_map(|v| v.values())
+                .filter(|v| !v.is_empty())
+                .collect();
+            let is_bool = !plain.is_empty() && plain.iter().all(|v| *v == "0" || *v == "1");
+            let format = if is_bool { "bool" } else { "string" };
+            new_field(id, name, format, multiple)
+        })
+        .collect();
+
+    for error in errors {
+        let field = fields
+            .iter_mut()
+            .filter(|field| error.starts_with(&field.name))
+            .max_by_key(|field| field.name.len());
+        match field {
+            Some(field) => field.is_required = Some(true),
+            None => {
+                let Some(name) = error.strip_suffix(" cannot be blank") else {
+                    continue;
+                };
+                if !ENTRY_LABELS.contains(&name) {
+                    let mut field = new_field(UNKNOWN_ID, name.to_string(), "string", false);
+                    field.is_required = Some(true);
+                    fields.push(field);
+                }
+            }
+        }
+    }
+
+    fields
+}
+
+fn new_field(id: i32, name: String, format: &str, multiple: bool) -> CustomField {
+    CustomField {
+        id,
+        name,
+        is_required: Some(false),
+        field_format: format.to_string(),
+        customized_type: "time_entry".to_string(),
+        multiple: Some(multiple),
+        default_value: None,
+        possible_values: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redmine::CustomValue;
+    use crate::track::duration::Minutes;
+
+    fn field(field_format: &str) -> CustomField {
+        CustomField {
+            id: 1,
+            name: "Field".to_string(),
+            is_required: Some(true),
+            field_format: field_format.to_string(),
+            customized_type: "time_entry".to_string(),
+            multiple: None,
+            default_value: None,
+            possible_values: vec![],
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 20).unwrap()
+    }
+
+    fn single(value: &str) -> Result<Option<FieldValue>, String> {
+        Ok(Some(FieldValue::Single(value.to_string())))
+    }
+
+    #[test]
+    fn test_parse_validates_the_format() {
+        assert_eq!(parse(&field("int"), "42", today()), single("42"));
+        assert!(parse(&field("int"), "4.2", today()).is_err());
+        assert_eq!(parse(&field("float"), "1,5", today()), single("1.5"));
+        assert_eq!(parse(&field("bool"), "yes", today()), single("1"));
+        assert_eq!(
+            parse(&field("date"), "monday", today()),
+            single("2024-03-18")
+        );
+        assert!(parse(&field("user"), "me", today()).is_err());
+        assert_eq!(parse(&field("string"), " text ", today()), single("text"));
+    }
+
+    #[test]
+    fn test_parse_empty_input() {
+        let mut optional = field("int");
+        optional.is_required = Some(false);
+        assert_eq!(parse(&optional, "", today()), Ok(None));
+        assert!(parse(&field("int"), "", today()).is_err());
+    }
+
+    #[test]
+    fn test_parse_possible_values() {
+        let mut list = field("key_value");
+        list.possible_values = vec![
+            PossibleValue {
+                value: "1".to_string(),
+                label: Some("Billable".to_string()),
+            },
+            PossibleValue {
+                value: "2".to_string(),
+                label: Some("Internal".to_string()),
+            },
+        ];
+        assert_eq!(parse(&list, "internal", today()), single("2"));
+        assert_eq!(parse(&list, "1", today()), single("1"));
+        assert!(parse(&list, "Other", today()).is_err());
+
+        list.multiple = Some(true);
+        assert_eq!(
+            parse(&list, "Billable, 2", today()),
+            Ok(Some(FieldValue::Multiple(vec![
+                "1".to_string(),
+                "2".to_string()
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_discover_fields_from_time_entries() {
+        let entry = |values: Vec<(i32, &str, FieldValue)>| {
+            let named = |id| Named { id, name: None };
+            TimeEntry {
+                id: 1,
+                user: named(1),
+                project: named(1),
+                issue: None,
+                activity: None,
+                hours: Minutes::new(60),
+                comments: None,
+                spent_on: "2024-03-18".to_string(),
+                custom_fields: values
+                    .into_iter()
+                    .map(|(id, name, value)| CustomValue {
+                        id,
+                        name: name.to_string(),
+                        value: Some(value),
+                    })
+                    .collect(),
+            }
+        };
+        let single = |v: &str| FieldValue::Single(v.to_string());
+        let entries = vec![
+            entry(vec![
+                (1, "Billable", single("1")),
+                (2, "Ticket", single("")),
+            ]),
+            entry(vec![
+                (1, "Billable", single("0")),
+                (3, "Tags", FieldValue::Multiple(vec!["a".to_string()])),
+            ]),
+        ];
+        let project_fields = vec![Named {
+            id: 4,
+            name: Some("Location".to_string()),
+        }];
+        let errors = vec![
+            "Hours cannot be blank".to_string(),
+            "Billable cannot be blank".to_string(),
+            "Cost center cannot be blank".to_string(),
+        ];
+
+        let fields = discover(&entries, &project_fields, &errors);
+
+        assert_eq!(fields.len(), 5);
+        assert_eq!(fields[0].field_format, "bool");
+        assert!(fields[0].is_required());
+        assert_eq!(fields[1].field_format, "string");
+        assert!(!fields[1].is_required());
+        assert!(fields[2].is_multiple());
+        assert_eq!(fields[3].name, "Location");
+        assert!(!fields[3].is_required());
+        assert_eq!(fields[4].name, "Cost center");
+        assert_eq!(fields[4].id, UNKNOWN_ID);
+        assert!(fields[4].is_required());
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+/// The output [Format] of lists and reports.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// A colored table for the terminal.
+    #[default]
+    Table,
+    /// An array of JSON objects, one per row.
+    Json,
+    /// Comma separated values with a header line.
+    Csv,
+    /// A Markdown table.
+    Markdown,
+}
+
+/// A [Sheet] is a plain table of values with named columns, that can be
+/// written in any of the machine readable [Format]s.
+///
+/// Hours are stored as numbers, missing values as `null`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// A single row of a [Sheet], serialized as an object with the keys in
+/// the order of the headers.
+struct Record<'a> {
+    headers: &'a [String],
+    values: &'a [Value],
+}
+
+impl Serialize for Record<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.headers.len()))?;
+        for (header, value) in self.headers.iter().zip(self.values) {
+            map.serialize_entry(header, value)?;
+        }
+        map.end()
+    }
+}
+
+impl Sheet {
+    /// Returns a new, empty [Sheet] with the given `headers`.
+    pub fn new(headers: &[&str]) -> Self {
+        Sheet {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: vec![],
+        }
+    }
+
+    /// Writes this sheet in the given `format`.
+    ///
+    /// Returns [None] for [Format::Table], which has to be rendered by the
+    /// caller.
+    pub fn write(&self, format: Format) -> anyhow::Result<Option<String>> {
+        match format {
+            Format::Table => Ok(None),
+            Format::Json => self.to_json().map(Some),
+            Format::Csv => Ok(Some(self.to_csv())),
+            Format::Markdown => Ok(Some(self.to_markdown())),
+        }
+    }
+
+    fn to_json(&self) -> anyhow::Result<String> {
+        let records: Vec<Record> = self
+            .rows
+            .iter()
+            .map(|values| Record {
+                headers: &self.headers,
+                values,
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&records)?)
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let headers: Vec<String> = self.headers.iter().map(|h| csv_field(h)).collect();
+        let _ = writeln!(csv, "{}", headers.join(","));
+        for row in &self.rows {
+            let fields: Vec<String> = row.iter().map(|v| csv_field(&plain(v))).collect();
+            let _ = writeln!(csv, "{}", fields.join(","));
+        }
+        csv
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        let _ = writeln!(markdown, "| {} |", self.headers.join(" | "));
+        let separators: Vec<&str> = self.headers.iter().map(|_| "---").collect();
+        let _ = writeln!(markdown, "| {} |", separators.join(" | "));
+        for row in &self.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|v| plain(v).replace('|', "\\|").replace('\n', " "))
+                .collect();
+            let _ = writeln!(markdown, "| {} |", cells.join(" | "));
+        }
+        markdown
+    }
+}
+
+/// Returns the given `value` without JSON quoting, `null` being empty.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::Null => "".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Quotes the given `field`, if it contains any special characters.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn sheet() -> Sheet {
+        let mut sheet = Sheet::new(&["id", "project", "hours"]);
+        sheet
+            .rows
+            .push(vec![json!(1), json!("Project, A"), json!(1.5)]);
+        sheet.rows.push(vec![json!(2), Value::Null, json!(2.0)]);
+        sheet
+    }
+
+    #[test]
+    fn test_csv_quotes_special_characters() {
+        let expected = "id,project,hours\n1,\"Project, A\",1.5\n2,,2.0\n";
+        assert_eq!(sheet().to_csv(), expected);
+    }
+
+    #[test]
+    fn test_json_keeps_column_order() {
+        let json = sheet().to_json().unwrap();
+        let id = json.find("\"id\"").unwrap();
+        let project = json.find("\"project\"").unwrap();
+        let hours = json.find("\"hours\"").unwrap();
+        assert!(id < project && project < hours);
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::redmine::TimeEntry;
+use crate::track::Error;
+
+/// The maximum number of created time entries, that are remembered.
+const MAX_ENTRIES: usize = 50;
+
+/// A [History] remembers the time entries created with track, most
+/// recent last.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct History {
+    #[serde(default)]
+    pub created: Vec<TimeEntry>,
+}
+
+impl History {
+    /// Load the history from `~/.track_history`.
+    ///
+    /// Returns an empty [History], if the file does not exist yet.
+    pub fn load() -> Result<Self, Error> {
+        Ok(super::load_json(".track_history")?.unwrap_or_default())
+    }
+
+    /// Store this history in `~/.track_history`.
+    pub fn store(&self) -> Result<(), Error> {
+        super::store_json(".track_history", self)
+    }
+
+    /// Remembers the newly created `entry`, forgetting the oldest ones
+    /// beyond [MAX_ENTRIES].
+    pub fn push(&mut self, entry: TimeEntry) {
+        self.created.push(entry);
+        if self.created.len() > MAX_ENTRIES {
+            let overflow = self.created.len() - MAX_ENTRIES;
+            self.created.drain(..overflow);
+        }
+    }
+
+    /// Returns the most recently created time entry.
+    pub fn last(&self) -> Option<&TimeEntry> {
+        self.created.last()
+    }
+
+    /// Forgets the time entry with the given `id`.
+    pub fn remove(&mut self, id: i32) {
+        self.created.retain(|entry| entry.id != id);
+    }
+}
@@ -1,144 +1,1366 @@
+mod cache;
+mod comment;
+pub mod date;
+pub mod duration;
+mod field;
+pub mod format;
+mod history;
+pub mod period;
+mod project;
+mod render;
 pub mod report;
+mod target;
+mod timer;
 mod ui;
 
-use chrono::{Datelike, Duration};
-use cli_table::{print_stdout, Cell, Color, Row, Style, Table};
-use report::Report;
+use anyhow::anyhow;
+use cache::IssueCache;
+use chrono::{Days, Local, NaiveDate};
+use clap::ValueEnum;
+use duration::{DurationFormat, HoursInput, Minutes, Rounding};
+use format::Format;
+use history::History;
+use period::Period;
+use report::{Report, SearchResult};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
+use std::path::PathBuf;
+use target::Target;
 
 use std::io::{BufReader, BufWriter};
 
+use crate::redmine::error::ApiError;
 use crate::redmine::request::Client;
-use crate::redmine::{CustomField, NewTimeEntry, User};
+use crate::redmine::{
+    Activities, Activity, CustomField, CustomValue, Issue, IssueStatus, Named, NewTimeEntry,
+    Project, TimeEntry, User, PROJECT_ACTIVE,
+};
 use crate::track::Error::{ApiKeyMissing, HomeDirNotFound};
 use std::io;
 use std::str::FromStr;
 use thiserror::Error;
+use timer::Timer;
 use url::Url;
 
-/// Track a new value of the time.
-pub fn track(client: &Client, yesterday: bool, id: Option<String>) -> Result<(), anyhow::Error> {
-    let (project, issue) = match id {
-        None => match ui::ask_for_issue() {
+/// The values of a time entry, that have been given on the command line.
+///
+/// Only the values missing here will be asked for, unless `no_input` is
+/// set, in which case missing values are reported as an error.
+#[derive(Debug, Clone, Default)]
+pub struct Preset {
+    /// The hours or an adjustment of the hours from the comment.
+    pub hours: Option<HoursInput>,
+    pub comment: Option<String>,
+    /// The name or id of the activity.
+    pub activity: Option<String>,
+    /// The identifier or id of the project.
+    pub project: Option<String>,
+    /// The custom field values as pairs of the field name and value.
+    pub fields: Vec<(String, String)>,
+    pub no_input: bool,
+}
+
+/// The filters of a search for issues.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    /// The identifier or id of the project, including its subprojects.
+    pub project: Option<String>,
+    pub status: StatusFilter,
+    /// `me`, the id or a part of the name of the assignee.
+    pub assignee: Option<String>,
+}
+
+/// Whether to search for open, closed or all issues.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    Open,
+    Closed,
+}
+
+/// Track a new value of the time on the day `spent_on`.
+///
+/// Days in the future are rejected, unless `force` is set.
+pub fn track(
+    client: &Client,
+    spent_on: NaiveDate,
+    force: bool,
+    id: Option<String>,
+    preset: &Preset,
+) -> Result<(), anyhow::Error> {
+    let today = chrono::Local::now().date_naive();
+    if spent_on > today && !force {
+        return Err(anyhow!(
+            "The date {} is in the future, please use --force to book on it anyway.",
+            spent_on
+        ));
+    }
+
+    if spent_on != today {
+        println!("Creating TimeEntry for {}", spent_on.format("%A, %Y-%m-%d"));
+    }
+
+    let (project, issue) = match (&preset.project, &id) {
+        (Some(project), _) => {
+            let project = find_project(client, project)?;
+            (Some(client.get_project(project.id)?), None)
+        }
+        (None, None) if preset.no_input => (None, None),
+        _ => ask_for_target(client, id, !preset.no_input)?,
+    };
+
+    book(client, project.as_ref(), issue, spent_on, None, preset)
+}
+
+/// Start a timer for the given issue `id` or ask for the issue or
+/// project to track.
+pub fn start(client: &Client, id: Option<String>, comment: Option<String>) -> anyhow::Result<()> {
+    if let Some(timer) = Timer::load()? {
+        return Err(anyhow!(
+            "A timer for {} is already running since {}. Please use `track stop` or `track cancel` first.",
+            timer.target(),
+            timer.started_at.format("%H:%M"),
+        ));
+    }
+
+    let (project, issue) = ask_for_target(client, id, true)?;
+    if project.is_none() && issue.is_none() {
+        return Err(anyhow!("No issue or project chosen."));
+    }
+
+    let timer = Timer {
+        issue_id: issue,
+        project: project.map(|p| Named {
+            id: p.id,
+            name: Some(p.name),
+        }),
+        started_at: chrono::Local::now(),
+        comment,
+    };
+    timer.store()?;
+    println!(
+        "Started tracking {} at {}.",
+        timer.target(),
+        timer.started_at.format("%H:%M")
+    );
+
+    Ok(())
+}
+
+/// Stop the running timer and create a time entry for it.
+pub fn stop(client: &Client) -> anyhow::Result<()> {
+    let timer = Timer::load()?.ok_or(anyhow!("There is no running timer."))?;
+    let comment = timer.comment_until(chrono::Local::now());
+    let project = project_for(client, timer.project.as_ref().map(|p| p.id), timer.issue_id)?;
+
+    book(
+        client,
+        project.as_ref(),
+        timer.issue_id,
+        timer.started_at.date_naive(),
+        Some(&comment),
+        &Preset::default(),
+    )?;
+    Timer::clear()?;
+
+    Ok(())
+}
+
+/// Print the running timer, if there is one, with the elapsed time in the
+/// duration format of the `config`.
+pub fn status(config: &Config) -> anyhow::Result<()> {
+    match Timer::load()? {
+        None => println!("There is no running timer."),
+        Some(timer) => {
+            let elapsed = Minutes::new((chrono::Local::now() - timer.started_at).num_minutes());
+            println!(
+                "Tracking {} since {} ({}h){}",
+                timer.target(),
+                timer.started_at.format("%H:%M"),
+                elapsed.format(config.duration_format),
+                timer
+                    .comment
+                    .as_ref()
+                    .map(|c| format!(": {}", c))
+                    .unwrap_or_default(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Discard the running timer, after the user confirmed it.
+pub fn cancel() -> anyhow::Result<()> {
+    let timer = match Timer::load()? {
+        None => {
+            println!("There is no running timer.");
+            return Ok(());
+        }
+        Some(timer) => timer,
+    };
+
+    let prompt = format!(
+        "Discard the timer for {} started at {}?",
+        timer.target(),
+        timer.started_at.format("%H:%M")
+    );
+    if ui::confirm(&prompt)? {
+        Timer::clear()?;
+    }
+
+    Ok(())
+}
+
+/// Returns the project and optionally the issue to track, based on the
+/// given `target` like `1234`, `#1234`, an issue URL or `@project`, or by
+/// asking the user. The project includes its activities and is the one
+/// of the issue, if there is one.
+///
+/// The issue is checked right away and if it is closed, the user may
+/// choose to book on its project instead, if `interactive` is set.
+fn ask_for_target(
+    client: &Client,
+    target: Option<String>,
+    interactive: bool,
+) -> anyhow::Result<(Option<Project>, Option<i32>)> {
+    let chosen = match target {
+        None => ask_for_issue(client, None)?,
+        Some(target) => {
+            let target = Target::from_str(&target).map_err(|err| anyhow!(err))?;
+            Some(find_target(client, target)?)
+        }
+    };
+
+    match chosen {
+        Some(Chosen::Issue(issue)) => check_issue(client, issue, interactive),
+        Some(Chosen::Project(project)) => Ok((Some(client.get_project(project.id)?), None)),
+        None => {
+            let mut projects = client.get_projects()?.projects;
+            let entries = client.get_recent_time_entries()?;
+            project::sort_by_usage(&mut projects, &entries.time_entries);
+            match ui::select_project(projects) {
+                Some(project) => Ok((Some(client.get_project(project.id)?), None)),
+                None => Ok((None, None)),
+            }
+        }
+    }
+}
+
+/// An issue or project, that has been chosen to book on.
+enum Chosen {
+    Issue(Issue),
+    Project(Project),
+}
+
+impl Chosen {
+    /// Returns the project and issue id of a time entry on this.
+    fn ids(&self) -> (Option<i32>, Option<i32>) {
+        match self {
+            Chosen::Issue(issue) => (None, Some(issue.id)),
+            Chosen::Project(project) => (Some(project.id), None),
+        }
+    }
+}
+
+/// Looks up the issue or project of the given `target`.
+fn find_target(client: &Client, target: Target) -> anyhow::Result<Chosen> {
+    match target {
+        Target::Issue(id) => {
+            let issue = find_issue(client, id)?.ok_or_else(|| not_found(id))?;
+            Ok(Chosen::Issue(issue))
+        }
+        Target::Project(needle) => Ok(Chosen::Project(find_project(client, &needle)?)),
+    }
+}
+
+/// Shows the given `issue` and checks, that time can be booked on it,
+/// returning its project and the issue to book on.
+///
+/// Issues of closed projects are rejected. For closed issues the user
+/// is asked, whether to book on their project instead, if `interactive`
+/// is set.
+fn check_issue(
+    client: &Client,
+    issue: Issue,
+    interactive: bool,
+) -> anyhow::Result<(Option<Project>, Option<i32>)> {
+    let project = client.get_project(issue.project.id)?;
+    if project
+        .status
+        .is_some_and(|status| status != PROJECT_ACTIVE)
+    {
+        return Err(anyhow!(
+            "The project {} of issue #{} is closed, no time can be booked on it.",
+            project.name,
+            issue.id
+        ));
+    }
+
+    if !is_closed(client, &issue)? {
+        return Ok((Some(project), Some(issue.id)));
+    }
+
+    let status = issue.status.as_ref().map(|s| s.name.as_str());
+    let warning = format!(
+        "Issue #{} is {}.",
+        issue.id,
+        status.unwrap_or("closed").to_lowercase()
+    );
+    if !interactive {
+        eprintln!("{}", warning);
+        return Ok((Some(project), Some(issue.id)));
+    }
+
+    let options = [
+        format!("Book on the project {} instead", project.name),
+        format!("Book on issue #{} anyway", issue.id),
+    ];
+    match ui::choose(&warning, &options) {
+        0 => Ok((Some(project), None)),
+        _ => Ok((Some(project), Some(issue.id))),
+    }
+}
+
+/// Returns whether the given `issue` has a closed status.
+fn is_closed(client: &Client, issue: &Issue) -> anyhow::Result<bool> {
+    let Some(status) = &issue.status else {
+        return Ok(false);
+    };
+
+    match status.is_closed {
+        Some(is_closed) => Ok(is_closed),
+        None => Ok(client
+            .get_issue_statuses()?
+            .issue_statuses
+            .iter()
+            .any(|s| s.id == status.id && s.is_closed.unwrap_or(false))),
+    }
+}
+
+/// Returns the issue with the given `id` or [None], if it does not exist
+/// or the user is not allowed to see it.
+fn find_issue(client: &Client, id: i32) -> anyhow::Result<Option<Issue>> {
+    match client.get_issue(id) {
+        Ok(issue) => Ok(Some(issue)),
+        Err(err) if matches!(err.downcast_ref::<ApiError>(), Some(ApiError::NotFound)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn not_found(id: i32) -> anyhow::Error {
+    anyhow!("There is no issue #{}, that you are allowed to see.", id)
+}
+
+/// Asks the user to type an issue or project, prefilled with the
+/// `initial` issue, or else to choose one of the issues booked recently
+/// and the open ones assigned to or watched by the user, and shows the
+/// chosen one with its project and status.
+///
+/// Returns [None] to choose a project from a list.
+fn ask_for_issue(client: &Client, initial: Option<i32>) -> anyhow::Result<Option<Chosen>> {
+    loop {
+        let chosen = match ui::type_target(initial) {
+            Some(target) => match find_target(client, target) {
+                Ok(chosen) => chosen,
+                Err(err) if err.downcast_ref::<ApiError>().is_none() => {
+                    eprintln!("{}", err);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            },
             None => {
-                let projects = client.get_projects()?;
-                let project = ui::select_project(projects);
-                (project, None)
+                let candidates = issue_candidates(client)?;
+                match ui::select_issue(&candidates) {
+                    Some(issue) => Chosen::Issue(issue.clone()),
+                    None => return Ok(None),
+                }
             }
+        };
+
+        match &chosen {
+            Chosen::Issue(issue) => println!("  {}", describe_issue(issue)),
+            Chosen::Project(project) => println!("  {}", project.name),
+        }
+        return Ok(Some(chosen));
+    }
+}
+
+/// Returns the issues to choose from, which are the ones booked recently,
+/// followed by the open ones assigned to or watched by the user.
+///
+/// If issues have been cached by `track issues sync`, they are taken from
+/// the [IssueCache] instead, with the open ones assigned to the user
+/// first, and only the recently booked issues missing from it are
+/// fetched.
+fn issue_candidates(client: &Client) -> anyhow::Result<Vec<Issue>> {
+    let cache = IssueCache::load().unwrap_or_else(|err| {
+        eprintln!("The cached issues are ignored: {}", err);
+        IssueCache::default()
+    });
 
-            issue => (None, issue),
+    let entries = client.get_recent_time_entries()?.time_entries;
+    let mut booked: Vec<i32> = vec![];
+    for issue in entries.iter().filter_map(|e| e.issue.as_ref()) {
+        if !booked.contains(&issue.id) {
+            booked.push(issue.id);
         }
+    }
+
+    let missing: Vec<String> = booked
+        .iter()
+        .filter(|id| !cache.issues.iter().any(|i| i.id == **id))
+        .map(|id| id.to_string())
+        .collect();
+    let mut candidates: Vec<Issue> = cache
+        .issues
+        .iter()
+        .filter(|i| booked.contains(&i.id))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        candidates.extend(client.get_issues(&missing)?.issues);
+    }
+    candidates.sort_by_key(|i| booked.iter().position(|id| *id == i.id));
+
+    if cache.issues.is_empty() {
+        candidates.extend(client.get_assigned_issues()?.issues);
+        candidates.extend(client.get_watched_issues()?.issues);
+    } else {
+        let user_id = client.config().user_id;
+        let (assigned, others): (Vec<Issue>, Vec<Issue>) =
+            cache.issues.into_iter().partition(|issue| {
+                let is_open = issue.status.as_ref().and_then(|s| s.is_closed) != Some(true);
+                is_open && issue.assigned_to.as_ref().is_some_and(|a| a.id == user_id)
+            });
+        candidates.extend(assigned);
+        candidates.extend(others);
+    }
+    let mut seen = HashSet::new();
+    candidates.retain(|issue| seen.insert(issue.id));
+
+    Ok(candidates)
+}
+
+/// Describes the `issue` like `#1234 Subject (Project, In Progress)`.
+fn describe_issue(issue: &Issue) -> String {
+    let mut details = vec![issue.project.name.clone().unwrap_or_default()];
+    details.extend(issue.status.as_ref().map(|s| s.name.clone()));
+    format!("#{} {} ({})", issue.id, issue.subject, details.join(", "))
+}
 
-        Some(issue) => (None, i32::from_str(&issue).ok())
+/// Asks for the remaining values of a time entry on the given `issue` or
+/// else the `project`, which have not been given in the `preset`, and
+/// creates it for the day `spent_on`. The `project` is the one of the
+/// issue, if there is one.
+///
+/// The comment prompt is prefilled with the given `comment`.
+fn book(
+    client: &Client,
+    project: Option<&Project>,
+    issue: Option<i32>,
+    spent_on: NaiveDate,
+    comment: Option<&str>,
+    preset: &Preset,
+) -> anyhow::Result<()> {
+    let mut missing = vec![];
+    let project_id = project.filter(|_| issue.is_none()).map(|p| p.id);
+    if project_id.is_none() && issue.is_none() {
+        missing.push("an issue id or --project".to_string());
+    }
+
+    let comment = match &preset.comment {
+        Some(comment) => comment.clone(),
+        None if preset.no_input => comment.unwrap_or_default().to_string(),
+        None => ui::ask_for_comment(comment),
     };
 
-    let comment = ui::ask_for_comment();
-    let hours = match ui::analyze_comments(comment.clone()) {
-        Some((from, to)) => {
-            let duration = to - from;
-            let hours = duration.num_hours() as f64;
-            let remaining_minutes = if hours <= 0.0 {
-                duration.num_minutes()
-            } else {
-                duration.num_minutes() % (duration.num_hours() * 60)
-            };
-
-            let minutes: f64 = (remaining_minutes as f64 / 15.0) * 0.25;
-            hours + minutes
+    let computed = hours_from_comment(&comment)?;
+    let from_ranges = preset.hours.is_none() && computed.is_some();
+    let hours = match (preset.hours, computed) {
+        (Some(input), computed) => Some(
+            input
+                .resolve(computed.unwrap_or(Minutes::ZERO))
+                .map_err(|err| anyhow!(err))?,
+        ),
+        (None, Some(hours)) => Some(hours),
+        (None, None) if preset.no_input => {
+            missing.push("--hours".to_string());
+            None
         }
-        None => ui::ask_for_hours(),
+        (None, None) => Some(ui::ask_for_hours(None, client.config().duration_format)),
     };
-    let activities = client.get_activities()?;
-    let activity = ui::select_activity(activities);
-    let custom_fields = client.get_custom_fields()?;
 
-    let mut custom_values = vec![];
-    for field in custom_fields.custom_fields {
-        if field.is_for_time_entry() && field.is_required() {
-            if let Some(value) = ui::ask_for_custom_field(field)? {
-                custom_values.push(value)
+    let activities = activities_for(client, project)?;
+    let preferred = match &preset.activity {
+        Some(_) => None,
+        None => preferred_activity(client, project, issue, &activities)?,
+    };
+    let activity = match &preset.activity {
+        Some(activity) => Some(find_activity(&activities, activity)?),
+        None if preset.no_input => {
+            let default = activities.activities.into_iter().find(|a| match preferred {
+                Some(preferred) => a.id == preferred,
+                None => a.is_default.unwrap_or(false),
+            });
+            if default.is_none() {
+                missing.push("--activity".to_string());
             }
+            default
+        }
+        None => Some(ui::select_activity(activities, preferred)),
+    };
+
+    let custom_values = collect_custom_values(client, preset, &mut missing)?;
+
+    let (Some(hours), Some(activity), true) = (hours, activity, missing.is_empty()) else {
+        return Err(anyhow!(
+            "The following values are missing: {}",
+            missing.join(", ")
+        ));
+    };
+    let hours = round_hours(client, hours, project, from_ranges);
+
+    let new_entry = NewTimeEntry {
+        issue_id: issue,
+        project_id,
+        hours,
+        comments: comment,
+        activity_id: activity.id,
+        custom_fields: custom_values,
+        spent_on: spent_on.format("%Y-%m-%d").to_string(),
+    };
+
+    let created = save_fixing_rejected(client, new_entry, !preset.no_input, |entry| {
+        client.create_time_entry(entry)
+    })?;
+    remember(created);
+
+    list(client, Period::Day(spent_on), false, None, Format::Table)?;
+
+    Ok(())
+}
+
+/// Saves the `entry` with `save`. If Redmine rejects some of its values
+/// and `interactive` is set, the user is asked for them again, until the
+/// entry is accepted.
+fn save_fixing_rejected<T>(
+    client: &Client,
+    mut entry: NewTimeEntry,
+    interactive: bool,
+    save: impl Fn(NewTimeEntry) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    loop {
+        let err = match save(entry.clone()) {
+            Ok(saved) => return Ok(saved),
+            Err(err) => err,
+        };
+
+        let errors = match err.downcast_ref::<ApiError>() {
+            Some(ApiError::Validation(errors)) if interactive => errors.clone(),
+            _ => return Err(err),
+        };
+
+        eprintln!("{}", err);
+        if !ask_for_rejected_values(client, &mut entry, &errors)? {
+            return Err(err);
+        }
+    }
+}
+
+/// Asks again for the values of the `entry`, that are mentioned in the
+/// validation `errors` of Redmine.
+///
+/// Errors are related to a value, if they start with its label, like
+/// `Hours cannot be blank`. Redmine translates its messages to the
+/// language of the user, so if none of them could be related, the user
+/// chooses the values to enter again. Returns `false`, if there are
+/// none, so asking again would not help.
+fn ask_for_rejected_values(
+    client: &Client,
+    entry: &mut NewTimeEntry,
+    errors: &[String],
+) -> anyhow::Result<bool> {
+    let custom_fields = bookable_fields(client)?;
+    let mut labels = vec!["Issue", "Comment", "Hours", "Activity"];
+    labels.extend(custom_fields.iter().map(|field| field.name.as_str()));
+
+    let mut rejected: Vec<String> = errors
+        .iter()
+        .filter_map(|error| {
+            labels
+                .iter()
+                .filter(|label| error.starts_with(*label))
+                .max_by_key(|label| label.len())
+                .map(|label| label.to_string())
+        })
+        .collect();
+    if rejected.is_empty() {
+        let options: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+        let chosen = ui::choose_many("Which values do you want to enter again?", &options)?;
+        rejected = chosen.into_iter().map(|i| options[i].clone()).collect();
+    }
+    let is_rejected = |label: &str| rejected.iter().any(|r| r == label);
+
+    if is_rejected("Issue") {
+        let chosen = ask_for_issue(client, entry.issue_id)?;
+        (entry.project_id, entry.issue_id) = chosen.map_or((entry.project_id, None), |c| c.ids());
+    }
+    if is_rejected("Comment") {
+        entry.comments = ui::ask_for_comment(Some(&entry.comments));
+    }
+    if is_rejected("Hours") {
+        entry.hours = ui::ask_for_hours(Some(entry.hours), client.config().duration_format);
+    }
+    if is_rejected("Activity") {
+        let project = project_for(client, entry.project_id, entry.issue_id)?;
+        let activities = activities_for(client, project.as_ref())?;
+        entry.activity_id = ui::select_activity(activities, Some(entry.activity_id)).id;
+    }
+    for field in custom_fields {
+        if !is_rejected(&field.name) {
+            continue;
         }
+
+        let id = field.id;
+        let current = entry
+            .custom_fields
+            .iter()
+            .find(|v| v.id == id)
+            .and_then(|v| v.value.clone());
+        entry.custom_fields.retain(|v| v.id != id);
+        entry
+            .custom_fields
+            .extend(ui::ask_for_custom_field(field, current)?);
     }
 
-    let today = match yesterday {
-        true => {
-            println!("Creating TimeEntry for yesterday");
-            chrono::Local::now() - Duration::days(1)
+    Ok(!rejected.is_empty())
+}
+
+/// Returns the project with the given identifier or id.
+fn find_project(client: &Client, needle: &str) -> anyhow::Result<Project> {
+    let projects = client.get_projects()?;
+    projects
+        .projects
+        .into_iter()
+        .find(|p| p.identifier == needle || p.id.to_string() == needle)
+        .ok_or_else(|| anyhow!("There is no project with the identifier or id {}.", needle))
+}
+
+/// Returns the activity with the given name or id.
+fn find_activity(activities: &Activities, needle: &str) -> anyhow::Result<Activity> {
+    activities
+        .activities
+        .iter()
+        .find(|a| a.name.eq_ignore_ascii_case(needle) || a.id.to_string() == needle)
+        .cloned()
+        .ok_or_else(|| {
+            let names = activities
+                .activities
+                .iter()
+                .map(|a| a.name.clone())
+                .collect::<Vec<String>>()
+                .join(", ");
+            anyhow!(
+                "There is no activity named {}, please use one of: {}",
+                needle,
+                names
+            )
+        })
+}
+
+/// Collects the custom values of a new time entry from the `preset`,
+/// asking for the required ones and the ones configured to be asked
+/// for, that have not been given.
+///
+/// If `preset.no_input` is set, the default values are used instead and
+/// missing required fields are added to `missing`.
+fn collect_custom_values(
+    client: &Client,
+    preset: &Preset,
+    missing: &mut Vec<String>,
+) -> anyhow::Result<Vec<CustomValue>> {
+    let custom_fields = bookable_fields(client)?;
+
+    for (name, _) in &preset.fields {
+        if !custom_fields
+            .iter()
+            .any(|f| f.name.eq_ignore_ascii_case(name))
+        {
+            return Err(anyhow!("There is no custom field named {}.", name));
         }
-        false => chrono::Local::now(),
+    }
+
+    let today = Local::now().date_naive();
+    let mut custom_values = vec![];
+    for field in custom_fields {
+        let given = preset
+            .fields
+            .iter()
+            .find(|(name, _)| field.name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str());
+        let value = match given {
+            Some(value) => field::parse(&field, value, today).map_err(|err| anyhow!(err))?,
+            None if !is_asked(client.config(), &field) => continue,
+            None if preset.no_input => match field.default_value() {
+                Some(value) => field::parse(&field, value, today).map_err(|err| anyhow!(err))?,
+                None if field.is_required() => {
+                    missing.push(format!("--field \"{}=...\"", field.name));
+                    continue;
+                }
+                None => continue,
+            },
+            None => {
+                if let Some(value) = ui::ask_for_custom_field(field, None)? {
+                    custom_values.push(value)
+                }
+                continue;
+            }
+        };
+
+        custom_values.push(CustomValue {
+            id: field.id,
+            name: field.name,
+            value,
+        });
+    }
+
+    Ok(custom_values)
+}
+
+/// Returns the custom fields of time entries, with the default values
+/// of the [Config] taking precedence over the ones of Redmine.
+fn time_entry_fields(client: &Client) -> anyhow::Result<Vec<CustomField>> {
+    let config = client.config();
+    Ok(client
+        .get_custom_fields()?
+        .custom_fields
+        .into_iter()
+        .filter(|field| field.is_for_time_entry())
+        .map(|mut field| {
+            if let Some(default) = config.field(&field.name).and_then(|f| f.default.clone()) {
+                field.default_value = Some(default);
+            }
+            field
+        })
+        .collect())
+}
+
+/// Returns the custom fields of time entries, that can be booked,
+/// because their id is known.
+fn bookable_fields(client: &Client) -> anyhow::Result<Vec<CustomField>> {
+    let mut fields = time_entry_fields(client)?;
+    fields.retain(|field| field.id != field::UNKNOWN_ID);
+    Ok(fields)
+}
+
+/// Returns whether the user should be asked for the given `field`,
+/// because it is required or configured to be asked for.
+fn is_asked(config: &Config, field: &CustomField) -> bool {
+    field.is_required() || config.field(&field.name).is_some_and(|f| f.ask)
+}
+
+/// Edit the time entry with the given `id`, by asking for all values
+/// again, prefilled with the current ones.
+pub fn edit(client: &Client, id: i32) -> anyhow::Result<()> {
+    let entry = client.get_time_entry(id)?;
+
+    let chosen = ask_for_issue(client, entry.issue.as_ref().map(|i| i.id))?;
+    let (project_id, issue) = chosen
+        .as_ref()
+        .map_or((Some(entry.project.id), None), |c| c.ids());
+    let project = match &chosen {
+        Some(Chosen::Issue(issue)) => client.get_project(issue.project.id)?,
+        _ => client.get_project(project_id.unwrap_or(entry.project.id))?,
     };
+    let comment = ui::ask_for_comment(entry.comments.as_deref());
+    let computed = hours_from_comment(&comment)?;
+    let format = client.config().duration_format;
+    let hours = computed.unwrap_or_else(|| ui::ask_for_hours(Some(entry.hours), format));
+    let hours = round_hours(client, hours, Some(&project), computed.is_some());
+    let activities = activities_for(client, Some(&project))?;
+    let activity = ui::select_activity(activities, entry.activity.as_ref().map(|a| a.id));
+    let custom_values = ask_for_custom_values(client, &entry.custom_fields)?;
 
-    let new_entry = NewTimeEntry {
+    let updated_entry = NewTimeEntry {
         issue_id: issue,
-        project_id: project.map(|p| p.id),
+        project_id,
         hours,
         comments: comment,
         activity_id: activity.id,
         custom_fields: custom_values,
-        spent_on: today.format("%Y-%m-%d").to_string(),
+        spent_on: entry.spent_on,
+    };
+
+    save_fixing_rejected(client, updated_entry, true, |entry| {
+        client.update_time_entry(id, entry)
+    })?;
+    println!("Time entry #{} has been updated.", id);
+
+    Ok(())
+}
+
+/// Delete the time entry with the given `id`, after the user confirmed it.
+pub fn delete(client: &Client, id: i32) -> anyhow::Result<()> {
+    let entry = client.get_time_entry(id)?;
+
+    let description = describe(&entry, client.config().duration_format);
+    if !ui::confirm(&format!("Delete {}?", description))? {
+        return Ok(());
+    }
+
+    client.delete_time_entry(id)?;
+    forget(id);
+    println!("Time entry #{} has been deleted.", id);
+
+    Ok(())
+}
+
+/// Delete the time entry, that has been created last with track, after
+/// the user confirmed it.
+pub fn undo(client: &Client) -> anyhow::Result<()> {
+    let history = History::load()?;
+    let entry = match history.last() {
+        None => {
+            println!("There is no time entry to undo.");
+            return Ok(());
+        }
+        Some(entry) => entry.clone(),
     };
 
-    client.create_time_entry(new_entry)?;
-    list(&client, false, false, false, None)?;
+    let description = describe(&entry, client.config().duration_format);
+    if !ui::confirm(&format!("Undo {}?", description))? {
+        return Ok(());
+    }
+
+    match client.delete_time_entry(entry.id) {
+        Ok(_) => println!("Time entry #{} has been deleted.", entry.id),
+        Err(err) if err.downcast_ref::<ApiError>() == Some(&ApiError::NotFound) => println!(
+            "Time entry #{} does not exist anymore, it has been removed from the history.",
+            entry.id
+        ),
+        Err(err) => return Err(err),
+    }
+    forget(entry.id);
 
     Ok(())
 }
 
+/// Remembers the `created` time entry for `track undo`.
+///
+/// The entry exists in Redmine already, so a history, that cannot be
+/// read or written, is only reported.
+fn remember(created: TimeEntry) {
+    let result = History::load().and_then(|mut history| {
+        history.push(created);
+        history.store()
+    });
+    if let Err(err) = result {
+        eprintln!(
+            "The time entry could not be remembered for `track undo`: {}",
+            err
+        );
+    }
+}
+
+/// Forgets the deleted time entry with the given `id`, only reporting a
+/// history, that cannot be read or written.
+fn forget(id: i32) {
+    let result = History::load().and_then(|mut history| {
+        history.remove(id);
+        history.store()
+    });
+    if let Err(err) = result {
+        eprintln!("The history of track could not be updated: {}", err);
+    }
+}
+
+/// Returns a short, human readable description of the given `entry`,
+/// with the hours in the given `format`.
+fn describe(entry: &TimeEntry, format: DurationFormat) -> String {
+    let issue = entry
+        .issue
+        .as_ref()
+        .map(|issue| format!(" #{}", issue.id))
+        .unwrap_or_default();
+    let comment = entry
+        .comments
+        .as_ref()
+        .filter(|c| !c.is_empty())
+        .map(|c| format!(" ({})", c))
+        .unwrap_or_default();
+
+    format!(
+        "{}h on {}{} from {}{}",
+        entry.hours.format(format),
+        entry.project.name.clone().unwrap_or_default(),
+        issue,
+        entry.spent_on,
+        comment,
+    )
+}
+
+/// Rounds the `hours` according to the [Rounding] policy of the
+/// `project` and shows both values, if they differ. The `hours` have
+/// been computed `from_ranges` in the comment, if set.
+fn round_hours(
+    client: &Client,
+    hours: Minutes,
+    project: Option<&Project>,
+    from_ranges: bool,
+) -> Minutes {
+    let rounding = rounding_for(client.config(), project, from_ranges);
+    let rounded = hours.round(rounding);
+    if rounded != hours {
+        let format = client.config().duration_format;
+        println!(
+            "Rounded {}h to {}h",
+            hours.format(format),
+            rounded.format(format)
+        );
+    }
+
+    rounded
+}
+
+/// Returns the given project or the project of the given issue,
+/// including its activities.
+fn project_for(
+    client: &Client,
+    project_id: Option<i32>,
+    issue: Option<i32>,
+) -> anyhow::Result<Option<Project>> {
+    let project_id = match (project_id, issue) {
+        (Some(project_id), _) => project_id,
+        (None, Some(issue)) => client.get_issue(issue)?.project.id,
+        (None, None) => return Ok(None),
+    };
+
+    Ok(Some(client.get_project(project_id)?))
+}
+
+/// Returns the activities enabled for the given `project`.
+///
+/// Redmine does not tell, which of them is the default of the project,
+/// so the global default is kept, if the project has an activity of the
+/// same name. Otherwise there is no default.
+fn activities_for(client: &Client, project: Option<&Project>) -> anyhow::Result<Activities> {
+    let global = client.get_activities()?;
+    let Some(project) = project.filter(|p| !p.time_entry_activities.is_empty()) else {
+        return Ok(global);
+    };
+
+    let mut activities = project.time_entry_activities.clone();
+    for activity in &mut activities {
+        activity.is_default = global
+            .activities
+            .iter()
+            .find(|a| a.name == activity.name)
+            .and_then(|a| a.is_default);
+    }
+
+    Ok(Activities { activities })
+}
+
+/// Returns the id of the activity to preselect for a time entry on the
+/// given `project` or `issue`.
+///
+/// That is the activity pinned in the [ProjectConfig] or else the one
+/// used last for the issue or the project, according to the latest time
+/// entries.
+fn preferred_activity(
+    client: &Client,
+    project: Option<&Project>,
+    issue: Option<i32>,
+    activities: &Activities,
+) -> anyhow::Result<Option<i32>> {
+    let Some(project) = project else {
+        return Ok(None);
+    };
+
+    let pinned = client
+        .config()
+        .project(project.id, Some(&project.identifier))
+        .and_then(|config| config.activity.as_deref());
+    if let Some(pinned) = pinned {
+        return Ok(Some(find_activity(activities, pinned)?.id));
+    }
+
+    let entries = client.get_recent_time_entries()?.time_entries;
+    let last_for_issue = issue.and_then(|issue| {
+        entries
+            .iter()
+            .find(|e| e.issue.as_ref().is_some_and(|i| i.id == issue))
+    });
+    let last = last_for_issue.or_else(|| entries.iter().find(|e| e.project.id == project.id));
+
+    let activity = last
+        .and_then(|entry| entry.activity.as_ref())
+        .and_then(|used| {
+            activities
+                .activities
+                .iter()
+                .find(|a| a.id == used.id || Some(&a.name) == used.name.as_ref())
+        });
+
+    Ok(activity.map(|a| a.id))
+}
+
+/// Returns the [Rounding] policy for the given `project`.
+///
+/// If no policy is configured at all, durations computed `from_ranges`
+/// in a comment are rounded with [Rounding::RANGES] and all others are
+/// kept exact.
+fn rounding_for(config: &Config, project: Option<&Project>, from_ranges: bool) -> Rounding {
+    let fallback = match (config.rounding, from_ranges) {
+        (Some(rounding), _) => rounding,
+        (None, true) => Rounding::RANGES,
+        (None, false) => Rounding::default(),
+    };
+
+    project
+        .and_then(|project| config.project(project.id, Some(&project.identifier)))
+        .and_then(|project| project.rounding)
+        .unwrap_or(fallback)
+}
+
+/// Calculates the hours from all time ranges in the `comment`.
+fn hours_from_comment(comment: &str) -> anyhow::Result<Option<Minutes>> {
+    comment::duration(comment).map_err(|err| anyhow!(err))
+}
+
+/// Asks the user for all required custom fields of a time entry and
+/// the ones configured to be asked for, prefilled with the `current`
+/// values.
+fn ask_for_custom_values(
+    client: &Client,
+    current: &[CustomValue],
+) -> anyhow::Result<Vec<CustomValue>> {
+    let mut custom_values = vec![];
+    for field in time_entry_fields(client)? {
+        if is_asked(client.config(), &field) {
+            let value = current
+                .iter()
+                .find(|v| v.id == field.id)
+                .and_then(|v| v.value.clone());
+            if let Some(value) = ui::ask_for_custom_field(field, value)? {
+                custom_values.push(value)
+            }
+        }
+    }
+
+    Ok(custom_values)
+}
+
+/// Discovers the custom fields of time entries and stores them in the
+/// [Config], so they are known without administrator rights.
+///
+/// The definitions of Redmine are used, if the user is allowed to see
+/// them. Otherwise they are guessed from the latest time entries, the
+/// fields included in their projects and the validation errors of an
+/// empty time entry.
+pub fn sync_fields(client: &Client) -> anyhow::Result<()> {
+    let fields: Vec<CustomField> = match client.get_defined_custom_fields() {
+        Ok(fields) => fields
+            .custom_fields
+            .into_iter()
+            .filter(|field| field.is_for_time_entry())
+            .collect(),
+        Err(err)
+            if matches!(
+                err.downcast_ref::<ApiError>(),
+                Some(ApiError::Forbidden | ApiError::NotFound)
+            ) =>
+        {
+            let entries = client.get_recent_time_entries()?.time_entries;
+            let mut project_ids: Vec<i32> = entries.iter().map(|e| e.project.id).collect();
+            if project_ids.is_empty() {
+                project_ids = client
+                    .get_projects()?
+                    .projects
+                    .iter()
+                    .map(|p| p.id)
+                    .collect();
+            }
+            project_ids.sort();
+            project_ids.dedup();
+
+            let mut project_fields = vec![];
+            for id in project_ids {
+                project_fields.extend(client.get_project(id)?.time_entry_custom_fields);
+            }
+
+            let errors = client.get_time_entry_errors()?;
+            field::discover(&entries, &project_fields, &errors)
+        }
+        Err(err) => return Err(err),
+    };
+
+    println!(
+        "Found {} custom fields of time entries, see `track fields list`.",
+        fields.len()
+    );
+    for field in fields.iter().filter(|field| field.id == field::UNKNOWN_ID) {
+        eprintln!(
+            "The id of the required field {} is unknown, please add it to ~/.track.",
+            field.name
+        );
+    }
+
+    let mut config = client.config().clone();
+    config.custom_fields = fields;
+    config.custom_fields_synced = true;
+    config.store()?;
+    Ok(())
+}
+
+/// Lists the custom fields of time entries with their default values.
+pub fn list_fields(client: &Client, format: Format) -> anyhow::Result<()> {
+    let fields = time_entry_fields(client)?;
+    render::renderer(format, client.config().duration_format).custom_fields(&fields)
+}
+
 /// Search for the given [`query`] using the given [`Config`] and
 /// display the result to the console.
-pub fn search(client: &Client, query: String, direct_track: bool) -> anyhow::Result<()> {
-    let results = client.search_tickets(query)?;
+///
+/// If `offline` is set, the issues are searched in the [IssueCache]
+/// instead of asking Redmine. If `interactive` is set, the results are
+/// offered in a picker instead and the chosen issue is tracked right
+/// away.
+pub fn search(
+    client: &Client,
+    query: String,
+    filter: &SearchFilter,
+    offline: bool,
+    direct_track: bool,
+    interactive: bool,
+    format: Format,
+) -> anyhow::Result<()> {
+    let results = match offline {
+        true => search_cache(client.config(), &query, filter)?,
+        false => search_redmine(client, query, filter)?,
+    };
 
-    let headers = vec![
-        "Id".cell().bold(true),
-        "Title".cell().bold(true),
-        "Url".cell().bold(true),
-    ];
-    let mut rows = vec![];
-    rows.push(headers.row());
-    for result in &results.results {
-        let cells = vec![
-            result.id.to_string().cell(),
-            result.title.clone().cell(),
-            result.url.clone().cell(),
-        ];
-        rows.push(cells.row())
-    }
-
-    let table = rows.table();
-
-    print_stdout(
-        table
-            .dimmed(true)
-            .foreground_color(Some(Color::Rgb(150, 150, 150))),
+    let chosen = if interactive {
+        ui::select_search_result(&results)
+    } else {
+        render::renderer(format, client.config().duration_format).search_results(&results)?;
+        results
+            .first()
+            .filter(|_| direct_track && results.len() == 1)
+    };
+
+    match chosen {
+        Some(result) => track(
+            client,
+            chrono::Local::now().date_naive(),
+            false,
+            Some(result.id.to_string()),
+            &Preset::default(),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Searches Redmine for issues with the `query` in their title, that
+/// match the `filter`.
+fn search_redmine(
+    client: &Client,
+    query: String,
+    filter: &SearchFilter,
+) -> anyhow::Result<Vec<SearchResult>> {
+    let results = client.search_tickets(
+        query,
+        filter.project.as_deref(),
+        filter.status == StatusFilter::Open,
     )?;
 
-    if direct_track && results.results.len() == 1 {
-        track(client, false, Some(results.results.first().unwrap().id.to_string()))
-    } else {
-        Ok(())
+    let ids: Vec<String> = results.results.iter().map(|r| r.id.to_string()).collect();
+    let issues = client.get_issues(&ids)?.issues;
+    let closed = match filter.status {
+        StatusFilter::Closed => closed_status_ids(client, &issues)?,
+        _ => HashSet::new(),
+    };
+
+    Ok(results
+        .results
+        .into_iter()
+        .filter_map(|result| {
+            let issue = issues.iter().find(|i| i.id == result.id)?;
+            let is_closed = issue.status.as_ref().map(|s| closed.contains(&s.id));
+            let matches = matches_filter(client.config(), filter, issue, is_closed);
+            matches.then(|| search_result(issue, result.url))
+        })
+        .collect())
+}
+
+/// Searches the [IssueCache] for issues fuzzy matching the `query`, that
+/// match the `filter`.
+///
+/// Without a connection to Redmine, the project of the `filter` is
+/// matched by id or name, excluding its subprojects.
+fn search_cache(
+    config: &Config,
+    query: &str,
+    filter: &SearchFilter,
+) -> anyhow::Result<Vec<SearchResult>> {
+    let cache = IssueCache::load()?;
+    if cache.issues.is_empty() {
+        return Err(anyhow!(
+            "There are no issues cached yet, please run `track issues sync` first."
+        ));
     }
 
+    Ok(cache
+        .search(query)
+        .into_iter()
+        .filter(|issue| match &filter.project {
+            Some(project) => {
+                issue.project.id.to_string() == *project
+                    || issue
+                        .project
+                        .name
+                        .as_ref()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(project))
+            }
+            None => true,
+        })
+        .filter(|issue| {
+            let is_closed = issue.status.as_ref().and_then(|s| s.is_closed);
+            matches_filter(config, filter, issue, is_closed)
+        })
+        .map(|issue| {
+            let url = config.base_url.join(&format!("issues/{}", issue.id));
+            search_result(issue, url.map(String::from).unwrap_or_default())
+        })
+        .collect())
+}
+
+fn search_result(issue: &Issue, url: String) -> SearchResult {
+    SearchResult {
+        id: issue.id,
+        subject: issue.subject.clone(),
+        project: issue.project.name.clone(),
+        status: issue.status.as_ref().map(|s| s.name.clone()),
+        url,
+    }
 }
 
-/// List the current.
-pub fn list(client: &Client, with_issues: bool, previous: bool, week: bool, ignore_custom_field: Option<String>) -> anyhow::Result<()> {
-    let day = match (previous, week) {
-        (true, false) => chrono::Local::now() - Duration::days(1),
-        (true, true) => chrono::Local::now() - Duration::days(7),
-        _ => chrono::Local::now(),
+/// Returns whether the `issue` matches the status and assignee of the
+/// `filter`, where `is_closed` is [None] for an unknown status.
+fn matches_filter(
+    config: &Config,
+    filter: &SearchFilter,
+    issue: &Issue,
+    is_closed: Option<bool>,
+) -> bool {
+    let matches_status = match filter.status {
+        StatusFilter::All => true,
+        StatusFilter::Open => is_closed != Some(true),
+        StatusFilter::Closed => is_closed == Some(true),
     };
+    let matches_assignee = match &filter.assignee {
+        Some(assignee) => is_assigned(config, issue, assignee),
+        None => true,
+    };
+
+    matches_status && matches_assignee
+}
 
-    let (from, to) = match week {
-        true => {
-            let weekday = day.weekday();
-            let start = day - Duration::days(weekday.num_days_from_monday() as i64);
-            let end = day + Duration::days(weekday.num_days_from_sunday() as i64);
-            (start, Some(end))
+/// Refreshes the [IssueCache] with all issues, that have been updated
+/// since the last refresh.
+///
+/// The first refresh only fetches the open issues updated within the
+/// last [cache::FIRST_SYNC_DAYS], instead of every issue ever created.
+/// Whether the status of an issue is closed is looked up, for Redmine
+/// versions not telling it.
+pub fn sync_issues(client: &Client) -> anyhow::Result<()> {
+    let mut cache = IssueCache::load()?;
+    let mut issues = match cache.updated_on.as_deref() {
+        Some(since) => client.get_updated_issues(since, false)?.issues,
+        None => {
+            let since = Local::now()
+                .date_naive()
+                .checked_sub_days(Days::new(cache::FIRST_SYNC_DAYS))
+                .expect("The first day to sync should be valid.");
+            let since = since.format("%Y-%m-%d").to_string();
+            client.get_updated_issues(&since, true)?.issues
         }
-        false => (day, None),
+    };
+
+    let mut statuses: Vec<&mut IssueStatus> = issues
+        .iter_mut()
+        .filter_map(|i| i.status.as_mut())
+        .collect();
+    if statuses.iter().any(|s| s.is_closed.is_none()) {
+        let known = client.get_issue_statuses()?.issue_statuses;
+        for status in statuses.iter_mut() {
+            let closed = known.iter().find(|k| k.id == status.id);
+            status.is_closed = closed.and_then(|k| k.is_closed);
+        }
+    }
+
+    let updated = issues.len();
+    cache.merge(issues);
+    cache.store()?;
+
+    println!(
+        "Updated {} issues, {} are cached now.",
+        updated,
+        cache.issues.len()
+    );
+    Ok(())
+}
+
+/// Returns the ids of all closed issue statuses of the given `issues`.
+fn closed_status_ids(client: &Client, issues: &[Issue]) -> anyhow::Result<HashSet<i32>> {
+    let statuses: Vec<IssueStatus> = issues.iter().filter_map(|i| i.status.clone()).collect();
+    let statuses = match statuses.iter().all(|s| s.is_closed.is_some()) {
+        true => statuses,
+        false => client.get_issue_statuses()?.issue_statuses,
+    };
+
+    Ok(statuses
+        .into_iter()
+        .filter(|s| s.is_closed.unwrap_or(false))
+        .map(|s| s.id)
+        .collect())
+}
+
+/// Returns whether the `issue` is assigned to the given `assignee`, which
+/// is `me`, the id or a part of the name of a user.
+fn is_assigned(config: &Config, issue: &Issue, assignee: &str) -> bool {
+    let Some(assigned_to) = &issue.assigned_to else {
+        return false;
+    };
+
+    match assignee {
+        "me" => assigned_to.id == config.user_id,
+        assignee if assignee.parse::<i32>().is_ok() => assigned_to.id.to_string() == assignee,
+        assignee => assigned_to
+            .name
+            .as_ref()
+            .is_some_and(|name| name.to_lowercase().contains(&assignee.to_lowercase())),
+    }
+}
+
+/// List the time entries of the given `period`.
+///
+/// A single day is shown as a list of its time entries, longer periods
+/// as a summary per project.
+pub fn list(
+    client: &Client,
+    period: Period,
+    with_issues: bool,
+    ignore_custom_field: Option<String>,
+    format: Format,
+) -> anyhow::Result<()> {
+    let (from, to) = match period {
+        Period::Day(day) => (day, None),
+        _ => (period.from(), Some(period.to())),
     };
 
     let time_entries = client.get_time_entries(from, to)?;
@@ -153,7 +1375,9 @@ pub fn list(client: &Client, with_issues: bool, previous: bool, week: bool, igno
                     // null values are treated as false
                     !entry.custom_fields.iter().any(|cf| {
                         cf.name == field_name && 
-                        cf.value.as_ref().map_or(false, |v| v == "1" || v.to_lowercase() == "true")
+                        cf.value.as_ref().is_some_and(|v| {
+                            v.values().iter().any(|v| *v == "1" || v.to_lowercase() == "true")
+                        })
                     })
                 })
                 .collect()
@@ -161,35 +1385,28 @@ pub fn list(client: &Client, with_issues: bool, previous: bool, week: bool, igno
         None => time_entries.time_entries,
     };
     
-    match week {
-        true => {
-            let issue_ids = &filtered_entries
+    match period {
+        Period::Week(_) | Period::Month(_) | Period::Range(_, _) => {
+            let mut issue_ids = filtered_entries
                 .iter()
                 .filter_map(|t| t.issue.as_ref().map(|i| i.id))
+                .collect::<Vec<i32>>();
+            issue_ids.sort();
+            issue_ids.dedup();
+            let issue_ids = issue_ids
+                .iter()
                 .map(|id| id.to_string())
                 .collect::<Vec<String>>();
             let issues = client.get_issues(&issue_ids)?;
             let report = Report::from_entries(&filtered_entries, &issues.issues);
 
-            let table =
-                report.to_table_struct(&(from + Duration::days(1)).date_naive(), with_issues);
-            print_stdout(
-                table
-                    .dimmed(true)
-                    .foreground_color(Some(Color::Rgb(150, 150, 150))),
-            )?;
-            Ok(())
+            let summary = report.summary(&period.columns());
+            render::renderer(format, client.config().duration_format).summary(&summary, with_issues)
         }
-        false => {
+        Period::Day(day) => {
             let report = Report::from_entries(&filtered_entries, &vec![]);
-            let daily_report = report.get_report_for_date(&from.date_naive());
-            let table = daily_report.to_table_struct();
-            print_stdout(
-                table
-                    .dimmed(true)
-                    .foreground_color(Some(Color::Rgb(150, 150, 150))),
-            )?;
-            Ok(())
+            let daily_report = report.get_report_for_date(&day);
+            render::renderer(format, client.config().duration_format).daily(&daily_report)
         }
     }
 }
@@ -197,7 +1414,7 @@ pub fn list(client: &Client, with_issues: bool, previous: bool, week: bool, igno
 /// A `Config` defines all parameters necessary, to connect to a Redmine server.
 ///
 /// It is stored in the users home directory as a .track file.
-#[derive(Serialize, Deserialize, Debug, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub key: String,
@@ -206,18 +1423,57 @@ pub struct Config {
     pub user_id: i32,
     #[serde(default)]
     pub custom_fields: Vec<CustomField>,
+    /// Whether the `custom_fields` have been discovered by `track fields
+    /// sync`, so they are used even if none were found.
+    #[serde(default)]
+    pub custom_fields_synced: bool,
+    /// How durations are displayed, e.g. `7.75` or `7:45`.
+    #[serde(default)]
+    pub duration_format: DurationFormat,
+    /// How computed and typed durations are rounded. Without it, only the
+    /// durations of time ranges in comments are rounded, with
+    /// [Rounding::RANGES].
+    #[serde(default)]
+    pub rounding: Option<Rounding>,
+    /// Settings for single projects, by their identifier or id.
+    #[serde(default)]
+    pub projects: BTreeMap<String, ProjectConfig>,
+    /// Settings for custom fields of time entries, by their name.
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldConfig>,
+}
+
+/// A [ProjectConfig] overrides the general settings of a [Config] for a
+/// single project.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfig {
+    pub rounding: Option<Rounding>,
+    /// The name or id of the activity to preselect for this project.
+    pub activity: Option<String>,
+}
+
+/// A [FieldConfig] defines how a single custom field is asked for.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldConfig {
+    /// Whether to ask for the field, even though it is optional.
+    #[serde(default)]
+    pub ask: bool,
+    /// The value to prefill or to use with `--no-input`.
+    pub default: Option<String>,
 }
 
 /// This type represents any error, that can happen while loading or storing
-/// a `Config`.
+/// a `Config` or the other files of track.
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Your home directory could not be found.")]
     HomeDirNotFound,
-    #[error("The configuration could not be read or written.")]
-    Io(#[from] io::Error),
-    #[error("The configuration could not be (de)/serialized.")]
-    Json(#[from] serde_json::Error),
+    #[error("The file {} could not be read or written.", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("The file {} could not be (de)/serialized, please fix or delete it.", .0.display())]
+    Json(PathBuf, #[source] serde_json::Error),
     #[error("The API key is missing, please create one in your user settings.")]
     ApiKeyMissing,
 }
@@ -238,11 +1494,33 @@ impl Config {
             login: user.login,
             user_id: user.id,
             custom_fields: vec![],
+            custom_fields_synced: false,
+            duration_format: DurationFormat::default(),
+            rounding: None,
+            projects: BTreeMap::new(),
+            fields: BTreeMap::new(),
         };
 
         Ok(config)
     }
 
+    /// Returns the settings of the project with the given `id` or
+    /// `identifier`, if there are any.
+    pub fn project(&self, id: i32, identifier: Option<&str>) -> Option<&ProjectConfig> {
+        self.projects
+            .get(&id.to_string())
+            .or_else(|| identifier.and_then(|identifier| self.projects.get(identifier)))
+    }
+
+    /// Returns the settings of the custom field with the given `name`,
+    /// ignoring the case.
+    pub fn field(&self, name: &str) -> Option<&FieldConfig> {
+        self.fields
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, field)| field)
+    }
+
     /// Load the configuration from `~/.track`.
     ///
     /// ## Example
@@ -252,17 +1530,7 @@ impl Config {
     /// ```
     ///
     pub fn load() -> Result<Option<Self>, Error> {
-        let home_dir = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
-        let track_file = home_dir.join(".track");
-
-        if !track_file.exists() {
-            return Ok(None);
-        }
-
-        let file = File::open(track_file)?;
-        let reader = BufReader::new(file);
-        let config = serde_json::from_reader(reader)?;
-        Ok(Some(config))
+        load_json(".track")
     }
 
     /// Store this configuration in `~/.track`.
@@ -275,12 +1543,49 @@ impl Config {
     /// ```
     ///
     pub fn store(&self) -> Result<(), Error> {
-        let home_dir = dirs::home_dir().ok_or(HomeDirNotFound)?;
-        let track_file = home_dir.join(".track");
-        let file = File::create(track_file)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer(writer, self)?;
+        store_json(".track", self)
+    }
+}
+
+/// Returns the path of the file with the given `name` in the users home
+/// directory.
+fn home_file(name: &str) -> Result<PathBuf, Error> {
+    let home_dir = dirs::home_dir().ok_or(HomeDirNotFound)?;
+    Ok(home_dir.join(name))
+}
+
+/// Reads the JSON file with the given `name` in the users home directory.
+///
+/// Returns [None], if the file does not exist yet.
+fn load_json<T: DeserializeOwned>(name: &str) -> Result<Option<T>, Error> {
+    let path = home_file(name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file = File::open(&path).map_err(|err| Error::Io(path.clone(), err))?;
+    let reader = BufReader::new(file);
+    serde_json::from_reader(reader)
+        .map(Some)
+        .map_err(|err| Error::Json(path, err))
+}
+
+/// Writes the `value` as JSON to the file with the given `name` in the
+/// users home directory.
+fn store_json<T: Serialize>(name: &str, value: &T) -> Result<(), Error> {
+    let path = home_file(name)?;
+    let file = File::create(&path).map_err(|err| Error::Io(path.clone(), err))?;
+    let writer = BufWriter::new(file);
+    serde_json::to_writer(writer, value).map_err(|err| Error::Json(path, err))
+}
 
-        Ok(())
+/// Removes the file with the given `name` in the users home directory,
+/// if it exists.
+fn remove_file(name: &str) -> Result<(), Error> {
+    let path = home_file(name)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|err| Error::Io(path, err))?;
     }
+
+    Ok(())
 }
@@ -0,0 +1,224 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::Serialize;
+
+/// A [Period] of days, for which time entries can be listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// A single day.
+    Day(NaiveDate),
+    /// The week starting at the given monday.
+    Week(NaiveDate),
+    /// The month starting at the given first day.
+    Month(NaiveDate),
+    /// All days from the first to the second date, inclusive.
+    Range(NaiveDate, NaiveDate),
+}
+
+/// A [Column] of a report, cumulating the hours of all its days.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub label: String,
+    pub days: Vec<NaiveDate>,
+}
+
+impl Column {
+    /// Returns a stable key for this column, which is the ISO date for
+    /// single days and the ISO week otherwise.
+    pub fn key(&self) -> String {
+        match self.days.as_slice() {
+            [day] => day.format("%Y-%m-%d").to_string(),
+            days => days
+                .first()
+                .map(|day| day.format("%G-W%V").to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Ranges up to this number of days are shown with a column per day,
+/// longer ones with a column per week.
+const MAX_DAY_COLUMNS: u64 = 14;
+
+impl Period {
+    /// Returns the week containing the given `day`.
+    pub fn week(day: NaiveDate) -> Self {
+        let monday = day - Days::new(day.weekday().num_days_from_monday() as u64);
+        Period::Week(monday)
+    }
+
+    /// Returns the month containing the given `day`.
+    pub fn month(day: NaiveDate) -> Self {
+        Period::Month(
+            day.with_day(1)
+                .expect("The first day of a month should exist."),
+        )
+    }
+
+    /// Parses an ISO week like `2024-W12`.
+    pub fn parse_week(input: &str) -> Result<Self, String> {
+        let invalid = || format!("The week {} should look like 2024-W12.", input);
+        let (year, week) = input.trim().split_once("-W").ok_or_else(invalid)?;
+        let year = year.parse::<i32>().map_err(|_| invalid())?;
+        let week = week.parse::<u32>().map_err(|_| invalid())?;
+        NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+            .map(Period::Week)
+            .ok_or_else(invalid)
+    }
+
+    /// Parses a month like `2024-03`.
+    pub fn parse_month(input: &str) -> Result<Self, String> {
+        NaiveDate::parse_from_str(&format!("{}-01", input.trim()), "%Y-%m-%d")
+            .map(Period::Month)
+            .map_err(|_| format!("The month {} should look like 2024-03.", input))
+    }
+
+    /// Returns the same kind of period `n` days, weeks or months earlier.
+    ///
+    /// A range is moved back by its own length. Periods before the
+    /// earliest supported date are an error.
+    pub fn back(self, n: u32) -> Result<Self, String> {
+        let days = |days: u64| days.checked_mul(n as u64).map(Days::new);
+        let moved = match self {
+            Period::Day(day) => days(1)
+                .and_then(|d| day.checked_sub_days(d))
+                .map(Period::Day),
+            Period::Week(monday) => days(7)
+                .and_then(|d| monday.checked_sub_days(d))
+                .map(Period::Week),
+            Period::Month(first) => first.checked_sub_months(Months::new(n)).map(Period::Month),
+            Period::Range(from, to) => {
+                let length = (to - from).num_days() as u64 + 1;
+                days(length).and_then(|d| {
+                    Some(Period::Range(
+                        from.checked_sub_days(d)?,
+                        to.checked_sub_days(d)?,
+                    ))
+                })
+            }
+        };
+
+        moved.ok_or_else(|| format!("The period cannot be moved back by {}.", n))
+    }
+
+    /// Returns the first day of this period.
+    pub fn from(&self) -> NaiveDate {
+        match self {
+            Period::Day(day) => *day,
+            Period::Week(monday) => *monday,
+            Period::Month(first) => *first,
+            Period::Range(from, _) => *from,
+        }
+    }
+
+    /// Returns the last day of this period.
+    pub fn to(&self) -> NaiveDate {
+        match self {
+            Period::Day(day) => *day,
+            Period::Week(monday) => *monday + Days::new(6),
+            Period::Month(first) => *first + Months::new(1) - Days::new(1),
+            Period::Range(_, to) => *to,
+        }
+    }
+
+    /// Returns all days of this period.
+    pub fn days(&self) -> Vec<NaiveDate> {
+        let to = self.to();
+        self.from()
+            .iter_days()
+            .take_while(|day| *day <= to)
+            .collect()
+    }
+
+    /// Returns the columns, in which a report for this period is shown.
+    ///
+    /// Weeks and short ranges get one column per day, months and long
+    /// ranges one column per week.
+    pub fn columns(&self) -> Vec<Column> {
+        let days = self.days();
+        match self {
+            Period::Day(_) | Period::Week(_) => days
+                .into_iter()
+                .map(|day| Column {
+                    label: day.weekday().to_string(),
+                    days: vec![day],
+                })
+                .collect(),
+            Period::Range(_, _) if days.len() as u64 <= MAX_DAY_COLUMNS => days
+                .into_iter()
+                .map(|day| Column {
+                    label: day.format("%a %d").to_string(),
+                    days: vec![day],
+                })
+                .collect(),
+            Period::Month(_) | Period::Range(_, _) => {
+                let mut columns: Vec<Column> = vec![];
+                for day in days {
+                    let label = format!("W{:02}", day.iso_week().week());
+                    match columns.last_mut() {
+                        Some(column) if column.label == label => column.days.push(day),
+                        _ => columns.push(Column {
+                            label,
+                            days: vec![day],
+                        }),
+                    }
+                }
+                columns
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_week_starts_on_monday() {
+        let period = Period::week(date(2024, 3, 20));
+        assert_eq!(period.from(), date(2024, 3, 18));
+        assert_eq!(period.to(), date(2024, 3, 24));
+        assert_eq!(Period::parse_week("2024-W12"), Ok(period));
+        assert!(Period::parse_week("2024-12").is_err());
+    }
+
+    #[test]
+    fn test_month_columns_are_weeks() {
+        let period = Period::parse_month("2024-02").unwrap();
+        assert_eq!(period.from(), date(2024, 2, 1));
+        assert_eq!(period.to(), date(2024, 2, 29));
+
+        let columns = period.columns();
+        let labels: Vec<&str> = columns.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["W05", "W06", "W07", "W08", "W09"]);
+        assert_eq!(columns[0].days.len(), 4);
+        assert_eq!(columns[4].days.len(), 4);
+    }
+
+    #[test]
+    fn test_back() {
+        assert_eq!(
+            Period::Day(date(2024, 3, 1)).back(1),
+            Ok(Period::Day(date(2024, 2, 29)))
+        );
+        assert_eq!(
+            Period::week(date(2024, 3, 20)).back(2),
+            Ok(Period::Week(date(2024, 3, 4)))
+        );
+        assert_eq!(
+            Period::month(date(2024, 3, 31)).back(1),
+            Ok(Period::Month(date(2024, 2, 1)))
+        );
+        assert_eq!(
+            Period::Range(date(2024, 3, 11), date(2024, 3, 13)).back(1),
+            Ok(Period::Range(date(2024, 3, 8), date(2024, 3, 10)))
+        );
+        assert!(Period::Day(date(2024, 3, 1)).back(u32::MAX).is_err());
+        assert!(Period::Range(date(2024, 3, 1), date(2024, 3, 31))
+            .back(u32::MAX)
+            .is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::redmine::{Project, TimeEntry};
+
+/// The separator between the names of a project and its parents.
+const SEPARATOR: &str = " › ";
+
+/// Returns the path of the `project` like `Parent › Child`, looking up
+/// its ancestors in `projects`.
+///
+/// Ancestors, that are not part of `projects`, end the path with the name
+/// Redmine returned for them.
+pub fn path(project: &Project, projects: &[Project]) -> String {
+    let by_id: HashMap<i32, &Project> = projects.iter().map(|p| (p.id, p)).collect();
+
+    let mut names = vec![project.name.clone()];
+    let mut parent = project.parent.as_ref();
+    while let Some(named) = parent {
+        match by_id.get(&named.id) {
+            Some(ancestor) if names.len() <= projects.len() => {
+                names.push(ancestor.name.clone());
+                parent = ancestor.parent.as_ref();
+            }
+            _ => {
+                names.extend(named.name.clone());
+                parent = None;
+            }
+        }
+    }
+
+    names.reverse();
+    names.join(SEPARATOR)
+}
+
+/// Sorts the `projects`, so the ones booked most often in the given
+/// `entries` come first, then the ones booked most recently, and all
+/// others by their path.
+///
+/// The `entries` are expected to be sorted from newest to oldest.
+pub fn sort_by_usage(projects: &mut [Project], entries: &[TimeEntry]) {
+    let mut usage: HashMap<i32, (usize, Reverse<usize>)> = HashMap::new();
+    for (position, entry) in entries.iter().enumerate() {
+        usage
+            .entry(entry.project.id)
+            .or_insert((0, Reverse(position)))
+            .0 += 1;
+    }
+
+    let paths: HashMap<i32, String> = projects.iter().map(|p| (p.id, path(p, projects))).collect();
+
+    projects.sort_by_cached_key(|p| (Reverse(usage.get(&p.id).copied()), paths[&p.id].clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redmine::Named;
+    use crate::track::duration::Minutes;
+
+    fn project(id: i32, name: &str, parent: Option<i32>) -> Project {
+        Project {
+            id,
+            name: name.to_string(),
+            identifier: name.to_lowercase(),
+            parent: parent.map(|id| Named {
+                id,
+                name: Some(format!("Project {}", id)),
+            }),
+            status: None,
+            time_entry_activities: vec![],
+            time_entry_custom_fields: vec![],
+        }
+    }
+
+    fn entry(project: i32) -> TimeEntry {
+        TimeEntry {
+            id: 1,
+            user: Named { id: 1, name: None },
+            project: Named {
+                id: project,
+                name: None,
+            },
+            issue: None,
+            activity: None,
+            hours: Minutes::new(60),
+            comments: None,
+            spent_on: "2024-03-18".to_string(),
+            custom_fields: vec![],
+        }
+    }
+
+    #[test]
+    fn test_path_contains_all_ancestors() {
+        let projects = vec![
+            project(1, "Customer", None),
+            project(2, "Website", Some(1)),
+            project(3, "Frontend", Some(2)),
+            project(4, "Orphan", Some(9)),
+        ];
+
+        assert_eq!(
+            path(&projects[2], &projects),
+            "Customer › Website › Frontend"
+        );
+        assert_eq!(path(&projects[0], &projects), "Customer");
+        assert_eq!(path(&projects[3], &projects), "Project 9 › Orphan");
+    }
+
+    #[test]
+    fn test_sort_by_usage() {
+        let mut projects = vec![
+            project(1, "A", None),
+            project(2, "B", None),
+            project(3, "C", None),
+            project(4, "D", None),
+        ];
+        let entries = vec![entry(3), entry(2), entry(2), entry(4)];
+
+        sort_by_usage(&mut projects, &entries);
+
+        let ids: Vec<i32> = projects.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![2, 3, 4, 1]);
+    }
+}
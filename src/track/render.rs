@@ -0,0 +1,531 @@
+use cli_table::format::Justify;
+use cli_table::{print_stdout, Cell, Color, Row, Style, Table, TableStruct};
+use serde_json::{json, Value};
+
+use crate::redmine::CustomField;
+use crate::track::duration::{DurationFormat, Minutes};
+use crate::track::format::{Format, Sheet};
+use crate::track::report::{DailyReport, SearchResult, Summary};
+
+/// A [Renderer] displays the plain results of track, like a [Summary] or
+/// a [DailyReport], in a specific way.
+pub trait Renderer {
+    /// Renders the `summary`, with a row per issue if `show_issues` is set.
+    fn summary(&self, summary: &Summary, show_issues: bool) -> anyhow::Result<()>;
+
+    /// Renders the time entries of a single day.
+    fn daily(&self, report: &DailyReport) -> anyhow::Result<()>;
+
+    /// Renders the results of a search.
+    fn search_results(&self, results: &[SearchResult]) -> anyhow::Result<()>;
+
+    /// Renders the custom fields of time entries.
+    fn custom_fields(&self, fields: &[CustomField]) -> anyhow::Result<()>;
+}
+
+/// Returns the [Renderer] for the given `format`, showing durations in
+/// the given `duration_format`, if the format is meant for humans.
+pub fn renderer(format: Format, duration_format: DurationFormat) -> Box<dyn Renderer> {
+    match format {
+        Format::Table => Box::new(TableRenderer { duration_format }),
+        format => Box::new(SheetRenderer { format }),
+    }
+}
+
+/// Renders colored tables to the terminal.
+pub struct TableRenderer {
+    duration_format: DurationFormat,
+}
+
+impl TableRenderer {
+    fn print(table: TableStruct) -> anyhow::Result<()> {
+        print_stdout(
+            table
+                .dimmed(true)
+                .foreground_color(Some(Color::Rgb(150, 150, 150))),
+        )?;
+        Ok(())
+    }
+}
+
+impl Renderer for TableRenderer {
+    fn summary(&self, summary: &Summary, show_issues: bool) -> anyhow::Result<()> {
+        let fg = Some(Color::Rgb(220, 220, 220));
+
+        let mut headers = vec!["".cell().bold(true), "∑".cell().justify(Justify::Right)];
+        for column in &summary.columns {
+            headers.push(column.label.clone().cell().foreground_color(fg));
+        }
+
+        let mut rows = vec![];
+        rows.push(headers.row());
+        for project in &summary.projects {
+            let mut cols = vec![];
+            cols.push(project.name.clone().cell().foreground_color(fg).bold(true));
+            cols.push(
+                project
+                    .total
+                    .fmt_zero_empty(self.duration_format)
+                    .cell()
+                    .justify(Justify::Right)
+                    .foreground_color(Some(Color::Cyan)),
+            );
+            for &hours in &project.hours {
+                cols.push(
+                    hours
+                        .fmt_zero_empty(self.duration_format)
+                        .cell()
+                        .foreground_color(fg)
+                        .justify(Justify::Right),
+                )
+            }
+            rows.push(cols.row());
+
+            if show_issues {
+                for issue in &project.issues {
+                    let mut cols = vec![];
+                    cols.push(
+                        format!("  [#{}] {}", issue.id, issue.subject)
+                            .cell()
+                            .foreground_color(fg),
+                    );
+                    cols.push(
+                        issue
+                            .total
+                            .fmt_zero_empty(self.duration_format)
+                            .cell()
+                            .justify(Justify::Right)
+                            .foreground_color(fg),
+                    );
+                    for &hours in &issue.hours {
+                        cols.push(
+                            hours
+                                .fmt_zero_empty(self.duration_format)
+                                .cell()
+                                .foreground_color(fg)
+                                .justify(Justify::Right),
+                        )
+                    }
+                    rows.push(cols.row());
+                }
+            }
+        }
+
+        let mut last_row = vec!["∑".cell()];
+        last_row.push(
+            summary
+                .total
+                .format(self.duration_format)
+                .cell()
+                .justify(Justify::Right)
+                .foreground_color(Some(Color::Cyan)),
+        );
+        for (column, &hours) in summary.columns.iter().zip(&summary.totals) {
+            let color = match hours {
+                _ if column.days.len() > 1 => Color::Cyan,
+                hours if hours <= Minutes::new(8 * 60) => Color::Green,
+                hours if hours <= Minutes::new(10 * 60) => Color::Yellow,
+                _ => Color::Red,
+            };
+            last_row.push(
+                hours
+                    .fmt_zero_empty(self.duration_format)
+                    .cell()
+                    .justify(Justify::Right)
+                    .foreground_color(Some(color)),
+            );
+        }
+
+        rows.push(last_row.row());
+        Self::print(rows.table())
+    }
+
+    /// Renders a `DailyReport` as a table like the following.
+    ///
+    /// ```text
+    /// +----+-----------+--------+-------+-------------------+
+    /// | Id | Project   | Issue  | Hours | Comment           |
+    /// +----+-----------+--------+-------+-------------------+
+    /// |  1 | Project A | #1234  | 9.00  | Worked on issue A |
+    /// |  2 | Project B |        | 3.00  |                   |
+    /// +----+-----------+--------+-------+-------------------+
+    /// ```
+    fn daily(&self, report: &DailyReport) -> anyhow::Result<()> {
+        let headers = vec![
+            "Id".cell().bold(true),
+            "Project".cell().bold(true),
+            "Issue".cell().bold(true),
+            "Subject".cell().bold(true),
+            format!(
+                "Hours (∑ {})",
+                report.total_hours.format(self.duration_format)
+            )
+            .cell()
+            .bold(true),
+            "Comment".cell().bold(true),
+        ];
+
+        let mut rows = vec![];
+        rows.push(headers.row());
+        for entry in report.entries.iter().rev() {
+            let cells = vec![
+                entry
+                    .id
+                    .cell()
+                    .foreground_color(Some(Color::Rgb(150, 150, 150)))
+                    .justify(Justify::Right),
+                entry.project.name.as_ref().unwrap_or(&"".into()).cell(),
+                entry
+                    .issue
+                    .as_ref()
+                    .and_then(|x| x.name.clone())
+                    .unwrap_or("".to_owned())
+                    .cell(),
+                entry
+                    .issue
+                    .as_ref()
+                    .map(|issue| format!("#{}", issue.id))
+                    .unwrap_or("".into())
+                    .cell()
+                    .foreground_color(Some(Color::Cyan))
+                    .justify(Justify::Right),
+                entry
+                    .hours
+                    .format(self.duration_format)
+                    .cell()
+                    .justify(Justify::Right),
+                entry
+                    .comments
+                    .as_ref()
+                    .unwrap_or(&"".into())
+                    .to_string()
+                    .cell()
+                    .foreground_color(Some(Color::Rgb(230, 230, 230))),
+            ];
+            rows.push(cells.row());
+        }
+
+        Self::print(rows.table())
+    }
+
+    fn search_results(&self, results: &[SearchResult]) -> anyhow::Result<()> {
+        let headers = vec![
+            "Id".cell().bold(true),
+            "Project".cell().bold(true),
+            "Status".cell().bold(true),
+            "Subject".cell().bold(true),
+            "Url".cell().bold(true),
+        ];
+        let mut rows = vec![];
+        rows.push(headers.row());
+        for result in results {
+            let cells = vec![
+                format!("#{}", result.id)
+                    .cell()
+                    .foreground_color(Some(Color::Cyan))
+                    .justify(Justify::Right),
+                result.project.clone().unwrap_or_default().cell(),
+                result.status.clone().unwrap_or_default().cell(),
+                result.subject.clone().cell(),
+                result.url.clone().cell(),
+            ];
+            rows.push(cells.row())
+        }
+
+        Self::print(rows.table())
+    }
+
+    fn custom_fields(&self, fields: &[CustomField]) -> anyhow::Result<()> {
+        let headers = vec![
+            "Id".cell().bold(true),
+            "Name".cell().bold(true),
+            "Format".cell().bold(true),
+            "Required".cell().bold(true),
+            "Multiple".cell().bold(true),
+            "Default".cell().bold(true),
+        ];
+        let mut rows = vec![];
+        rows.push(headers.row());
+        for field in fields {
+            let cells = vec![
+                field.id.cell().justify(Justify::Right),
+                field.name.clone().cell(),
+                field.field_format.clone().cell(),
+                yes_or_empty(field.is_required()).cell(),
+                yes_or_empty(field.is_multiple()).cell(),
+                field.default_value().unwrap_or_default().cell(),
+            ];
+            rows.push(cells.row())
+        }
+
+        Self::print(rows.table())
+    }
+}
+
+fn yes_or_empty(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        ""
+    }
+}
+
+/// Renders flat [Sheet]s in one of the machine readable [Format]s.
+pub struct SheetRenderer {
+    format: Format,
+}
+
+impl SheetRenderer {
+    fn print(&self, sheet: Sheet) -> anyhow::Result<()> {
+        if let Some(output) = sheet.write(self.format)? {
+            print!("{}", output);
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for SheetRenderer {
+    /// Renders a row per project and optionally per issue, for each ISO
+    /// date or week of the `summary`.
+    fn summary(&self, summary: &Summary, show_issues: bool) -> anyhow::Result<()> {
+        self.print(summary_sheet(summary, show_issues))
+    }
+
+    fn daily(&self, report: &DailyReport) -> anyhow::Result<()> {
+        let mut sheet = Sheet::new(&[
+            "id",
+            "spent_on",
+            "project_id",
+            "project",
+            "issue_id",
+            "activity",
+            "hours",
+            "comment",
+        ]);
+
+        for entry in report.entries.iter().rev() {
+            sheet.rows.push(vec![
+                json!(entry.id),
+                json!(entry.spent_on),
+                json!(entry.project.id),
+                json!(entry.project.name),
+                json!(entry.issue.as_ref().map(|issue| issue.id)),
+                json!(entry.activity.as_ref().and_then(|a| a.name.clone())),
+                json!(entry.hours),
+                json!(entry.comments),
+            ]);
+        }
+
+        self.print(sheet)
+    }
+
+    fn search_results(&self, results: &[SearchResult]) -> anyhow::Result<()> {
+        let mut sheet = Sheet::new(&["id", "project", "status", "subject", "url"]);
+        for result in results {
+            sheet.rows.push(vec![
+                json!(result.id),
+                json!(result.project),
+                json!(result.status),
+                json!(result.subject),
+                json!(result.url),
+            ]);
+        }
+
+        self.print(sheet)
+    }
+
+    fn custom_fields(&self, fields: &[CustomField]) -> anyhow::Result<()> {
+        let mut sheet = Sheet::new(&[
+            "id",
+            "name",
+            "format",
+            "required",
+            "multiple",
+            "default",
+            "possible_values",
+        ]);
+        for field in fields {
+            let possible_values: Vec<&str> = field
+                .possible_values
+                .iter()
+                .map(|v| v.value.as_str())
+                .collect();
+            sheet.rows.push(vec![
+                json!(field.id),
+                json!(field.name),
+                json!(field.field_format),
+                json!(field.is_required()),
+                json!(field.is_multiple()),
+                json!(field.default_value()),
+                json!(possible_values.join(",")),
+            ]);
+        }
+
+        self.print(sheet)
+    }
+}
+
+/// Returns the `summary` in long format with a row per project, and
+/// issue if `show_issues` is set, and column of the period, so the
+/// headers are the same for every period.
+///
+/// The period is the ISO date or week of the column. Hours booked on a
+/// project without an issue have no `issue_id`, empty cells are skipped.
+fn summary_sheet(summary: &Summary, show_issues: bool) -> Sheet {
+    let mut sheet = Sheet::new(&[
+        "project_id",
+        "project",
+        "issue_id",
+        "issue",
+        "period",
+        "hours",
+    ]);
+
+    for project in &summary.projects {
+        for (index, column) in summary.columns.iter().enumerate() {
+            let mut row = |issue_id: Value, issue: Value, hours: Minutes| {
+                if !hours.is_zero() {
+                    sheet.rows.push(vec![
+                        json!(project.id),
+                        json!(project.name),
+                        issue_id,
+                        issue,
+                        json!(column.key()),
+                        json!(hours),
+                    ]);
+                }
+            };
+
+            if !show_issues {
+                row(Value::Null, Value::Null, project.hours[index]);
+                continue;
+            }
+            for issue in &project.issues {
+                row(json!(issue.id), json!(issue.subject), issue.hours[index]);
+            }
+            row(Value::Null, Value::Null, project.without_issue[index]);
+        }
+    }
+
+    sheet
+}
+
+trait DisplayExt {
+    fn fmt_zero_empty(self, format: DurationFormat) -> String;
+}
+
+impl DisplayExt for Minutes {
+    fn fmt_zero_empty(self, format: DurationFormat) -> String {
+        if self.is_zero() {
+            "".into()
+        } else {
+            self.format(format)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::track::period::Period;
+    use crate::track::report::{IssueRow, ProjectRow};
+
+    fn hours(hours: i64) -> Minutes {
+        Minutes::new(hours * 60)
+    }
+
+    #[test]
+    fn test_summary_sheet_has_a_row_per_day() {
+        let monday = NaiveDate::from_ymd_opt(2022, 1, 3).unwrap();
+        let columns = Period::week(monday).columns();
+        let summary = Summary {
+            projects: vec![ProjectRow {
+                id: 1,
+                name: "Project A".to_string(),
+                total: hours(9),
+                hours: vec![
+                    hours(4),
+                    hours(0),
+                    hours(5),
+                    hours(0),
+                    hours(0),
+                    hours(0),
+                    hours(0),
+                ],
+                without_issue: vec![hours(0); 7],
+                issues: vec![],
+            }],
+            totals: vec![
+                hours(4),
+                hours(0),
+                hours(5),
+                hours(0),
+                hours(0),
+                hours(0),
+                hours(0),
+            ],
+            total: hours(9),
+            columns,
+        };
+
+        let sheet = summary_sheet(&summary, false);
+
+        assert_eq!(
+            sheet.headers,
+            vec![
+                "project_id",
+                "project",
+                "issue_id",
+                "issue",
+                "period",
+                "hours"
+            ]
+        );
+        assert_eq!(sheet.rows.len(), 2);
+        assert_eq!(sheet.rows[0][4], json!("2022-01-03"));
+        assert_eq!(sheet.rows[0][5], json!(4.0));
+        assert_eq!(sheet.rows[1][4], json!("2022-01-05"));
+        assert_eq!(sheet.rows[1][5], json!(5.0));
+    }
+
+    #[test]
+    fn test_summary_sheet_splits_issues_from_the_project() {
+        let monday = NaiveDate::from_ymd_opt(2022, 1, 3).unwrap();
+        let columns = Period::month(monday).columns();
+        let mut project_hours = vec![hours(0); columns.len()];
+        project_hours[1] = hours(3);
+        let mut issue_hours = vec![hours(0); columns.len()];
+        issue_hours[1] = hours(2);
+        let mut without_issue = vec![hours(0); columns.len()];
+        without_issue[1] = hours(1);
+        let summary = Summary {
+            projects: vec![ProjectRow {
+                id: 1,
+                name: "Project A".to_string(),
+                total: hours(3),
+                hours: project_hours.clone(),
+                without_issue,
+                issues: vec![IssueRow {
+                    id: 1234,
+                    subject: "Login".to_string(),
+                    total: hours(2),
+                    hours: issue_hours,
+                }],
+            }],
+            totals: project_hours,
+            total: hours(3),
+            columns,
+        };
+
+        let sheet = summary_sheet(&summary, true);
+
+        assert_eq!(sheet.rows.len(), 2);
+        assert_eq!(sheet.rows[0][2], json!(1234));
+        assert_eq!(sheet.rows[0][4], json!("2022-W01"));
+        assert_eq!(sheet.rows[0][5], json!(2.0));
+        assert_eq!(sheet.rows[1][2], Value::Null);
+        assert_eq!(sheet.rows[1][5], json!(1.0));
+    }
+}
@@ -1,21 +1,24 @@
 use std::collections::HashMap;
 
-use chrono::{Datelike, Days, NaiveDate};
-use cli_table::format::Justify;
-use cli_table::{Cell, Color, Row, Style, Table, TableStruct};
+use chrono::NaiveDate;
+use serde::Serialize;
 
 use crate::redmine::{Issue, TimeEntry};
+use crate::track::duration::Minutes;
+use crate::track::period::Column;
 
 /// A [Report] represents the result of cumulating a [Vec] of [TimeEntry]s.
 #[derive(Debug)]
 pub struct Report {
     projects: HashMap<i32, String>,
-    cumulative_hours: HashMap<(NaiveDate, i32), f64>,
-    cumulative_issue_hours: HashMap<(NaiveDate, i32), f64>,
+    cumulative_hours: HashMap<(NaiveDate, i32), Minutes>,
+    cumulative_issue_hours: HashMap<(NaiveDate, i32), Minutes>,
+    /// The hours booked on a project without an issue, per day.
+    cumulative_project_only_hours: HashMap<(NaiveDate, i32), Minutes>,
     entries_per_day: HashMap<NaiveDate, Vec<TimeEntry>>,
-    hours_per_project: HashMap<i32, f64>,
-    hours_at: HashMap<NaiveDate, f64>,
-    hours_per_issue: HashMap<i32, f64>,
+    hours_per_project: HashMap<i32, Minutes>,
+    hours_at: HashMap<NaiveDate, Minutes>,
+    hours_per_issue: HashMap<i32, Minutes>,
     issues_per_project: HashMap<i32, Vec<(i32, String)>>,
 }
 
@@ -27,6 +30,7 @@ impl Report {
         let mut hours_per_issue = HashMap::new();
         let mut cumulative_hours = HashMap::new();
         let mut cumulative_issue_hours = HashMap::new();
+        let mut cumulative_project_only_hours = HashMap::new();
         let mut projects = HashMap::new();
         let mut hours_per_project = HashMap::new();
         let mut hours_at = HashMap::new();
@@ -44,7 +48,7 @@ impl Report {
             let spent_on = NaiveDate::parse_from_str(&time_entry.spent_on, "%Y-%m-%d").unwrap();
 
             let key = (spent_on, time_entry.project.id);
-            *cumulative_hours.entry(key).or_insert(0.0) += time_entry.hours;
+            *cumulative_hours.entry(key).or_insert(Minutes::ZERO) += time_entry.hours;
 
             entries_per_day
                 .entry(spent_on)
@@ -53,15 +57,22 @@ impl Report {
 
             *hours_per_project
                 .entry(time_entry.project.id)
-                .or_insert(0.0) += time_entry.hours;
+                .or_insert(Minutes::ZERO) += time_entry.hours;
 
-            *hours_at.entry(spent_on).or_insert(0.0) += time_entry.hours;
+            *hours_at.entry(spent_on).or_insert(Minutes::ZERO) += time_entry.hours;
 
-            if let Some(issue_id) = time_entry.issue.as_ref().map(|t| t.id) {
-                *hours_per_issue.entry(issue_id).or_insert(0.0) += time_entry.hours;
+            match time_entry.issue.as_ref().map(|t| t.id) {
+                Some(issue_id) => {
+                    *hours_per_issue.entry(issue_id).or_insert(Minutes::ZERO) += time_entry.hours;
 
-                let key = (spent_on, issue_id);
-                *cumulative_issue_hours.entry(key).or_insert(0.0) += time_entry.hours;
+                    let key = (spent_on, issue_id);
+                    *cumulative_issue_hours.entry(key).or_insert(Minutes::ZERO) += time_entry.hours;
+                }
+                None => {
+                    *cumulative_project_only_hours
+                        .entry(key)
+                        .or_insert(Minutes::ZERO) += time_entry.hours;
+                }
             }
 
             projects.entry(time_entry.project.id).or_insert(
@@ -82,21 +93,22 @@ impl Report {
             hours_per_issue,
             issues_per_project,
             cumulative_issue_hours,
+            cumulative_project_only_hours,
         }
     }
 
-    pub fn get_or_zero(&self, day: &NaiveDate, project_id: i32) -> f64 {
+    pub fn get_or_zero(&self, day: &NaiveDate, project_id: i32) -> Minutes {
         *self
             .cumulative_hours
             .get(&(*day, project_id))
-            .unwrap_or(&0.0)
+            .unwrap_or(&Minutes::ZERO)
     }
 
-    pub fn get_issue_or_zero(&self, day: &NaiveDate, issue_id: i32) -> f64 {
+    pub fn get_issue_or_zero(&self, day: &NaiveDate, issue_id: i32) -> Minutes {
         *self
             .cumulative_issue_hours
             .get(&(*day, issue_id))
-            .unwrap_or(&0.0)
+            .unwrap_or(&Minutes::ZERO)
     }
 
     /// Returns a new [DailyReport] for the given [needle].
@@ -104,7 +116,7 @@ impl Report {
     /// If there are no time entries for the day, will return an
     /// empty [DailyReport].
     pub fn get_report_for_date(&self, needle: &NaiveDate) -> DailyReport {
-        let total_hours = *self.hours_at.get(needle).unwrap_or(&0.0);
+        let total_hours = *self.hours_at.get(needle).unwrap_or(&Minutes::ZERO);
 
         let entries = self.entries_per_day.get(needle).unwrap_or(&vec![]).clone();
 
@@ -114,251 +126,172 @@ impl Report {
         }
     }
 
-    pub fn to_table_struct(&self, needle: &NaiveDate, show_issues: bool) -> TableStruct {
-        let monday = needle
-            .monday_of_week()
-            .unwrap_or_else(|| panic!("The monday of {needle} should exist."));
-        let sunday = needle
-            .sunday_of_week()
-            .unwrap_or_else(|| panic!("The sunday of {needle} should exist."));
-
-        let days: Vec<NaiveDate> = monday
-            .iter_days()
-            .take_while(|day| Some(*day) != sunday.succ_opt())
+    /// Returns the hours of the given project, cumulated over the days of
+    /// the `column`.
+    fn get_column_or_zero(&self, column: &Column, project_id: i32) -> Minutes {
+        column
+            .days
+            .iter()
+            .map(|day| self.get_or_zero(day, project_id))
+            .sum()
+    }
+
+    /// Returns the hours booked on the given project without an issue,
+    /// cumulated over the days of the `column`.
+    fn get_project_only_column_or_zero(&self, column: &Column, project_id: i32) -> Minutes {
+        column
+            .days
+            .iter()
+            .map(|day| {
+                *self
+                    .cumulative_project_only_hours
+                    .get(&(*day, project_id))
+                    .unwrap_or(&Minutes::ZERO)
+            })
+            .sum()
+    }
+
+    /// Returns the hours of the given issue, cumulated over the days of
+    /// the `column`.
+    fn get_issue_column_or_zero(&self, column: &Column, issue_id: i32) -> Minutes {
+        column
+            .days
+            .iter()
+            .map(|day| self.get_issue_or_zero(day, issue_id))
+            .sum()
+    }
+
+    /// Cumulates this report into a [Summary] with the hours of each
+    /// project and issue in each of the `columns`.
+    pub fn summary(&self, columns: &[Column]) -> Summary {
+        let projects = self
+            .sorted_projects()
+            .into_iter()
+            .map(|(project_id, name)| {
+                let issues = self
+                    .issues_per_project
+                    .get(&project_id)
+                    .unwrap_or(&vec![])
+                    .iter()
+                    .map(|(issue_id, subject)| IssueRow {
+                        id: *issue_id,
+                        subject: subject.clone(),
+                        total: *self.hours_per_issue.get(issue_id).unwrap_or(&Minutes::ZERO),
+                        hours: columns
+                            .iter()
+                            .map(|column| self.get_issue_column_or_zero(column, *issue_id))
+                            .collect(),
+                    })
+                    .collect();
+
+                ProjectRow {
+                    id: project_id,
+                    name,
+                    total: *self
+                        .hours_per_project
+                        .get(&project_id)
+                        .unwrap_or(&Minutes::ZERO),
+                    hours: columns
+                        .iter()
+                        .map(|column| self.get_column_or_zero(column, project_id))
+                        .collect(),
+                    without_issue: columns
+                        .iter()
+                        .map(|column| self.get_project_only_column_or_zero(column, project_id))
+                        .collect(),
+                    issues,
+                }
+            })
             .collect();
 
-        let fg = Some(Color::Rgb(220, 220, 220));
+        let totals = columns
+            .iter()
+            .map(|column| {
+                column
+                    .days
+                    .iter()
+                    .map(|day| *self.hours_at.get(day).unwrap_or(&Minutes::ZERO))
+                    .sum()
+            })
+            .collect();
 
-        let mut headers = vec!["".cell().bold(true), "∑".cell().justify(Justify::Right)];
-        for day in &days {
-            headers.push(day.weekday().to_string().cell().foreground_color(fg));
+        Summary {
+            columns: columns.to_vec(),
+            projects,
+            totals,
+            total: self.hours_per_project.values().sum(),
         }
+    }
 
-        let mut rows = vec![];
-        rows.push(headers.row());
-        let mut total_hours: f64 = 0.0;
+    /// Returns all projects of this report, sorted by their name.
+    fn sorted_projects(&self) -> Vec<(i32, String)> {
         let mut projects = self
             .projects
             .iter()
             .map(|(a, b)| (*a, b.clone()))
             .collect::<Vec<(i32, String)>>();
         projects.sort_by(|(_, a), (_, b)| a.cmp(b));
-        for (project_id, project) in projects {
-            let mut cols = vec![];
-            cols.push(project.cell().foreground_color(fg).bold(true));
-            let project_hours = self.hours_per_project.get(&project_id).unwrap_or(&0.0);
-            total_hours += project_hours;
-            cols.push(
-                project_hours
-                    .fmt_zero_empty()
-                    .cell()
-                    .justify(Justify::Right)
-                    .foreground_color(Some(Color::Cyan)),
-            );
-            for day in &days {
-                let hours = self.get_or_zero(day, project_id);
-                cols.push(
-                    hours
-                        .fmt_zero_empty()
-                        .cell()
-                        .foreground_color(fg)
-                        .justify(Justify::Right),
-                )
-            }
-            rows.push(cols.row());
-
-            if show_issues {
-                for (issue, subject) in self.issues_per_project.get(&project_id).unwrap_or(&vec![])
-                {
-                    let mut cols = vec![];
-                    cols.push(
-                        format!("  [#{}] {}", issue, subject)
-                            .cell()
-                            .foreground_color(fg),
-                    );
-                    let issue_hours = self.hours_per_issue.get(issue).unwrap_or(&0.0);
-                    cols.push(
-                        issue_hours
-                            .fmt_zero_empty()
-                            .cell()
-                            .justify(Justify::Right)
-                            .foreground_color(fg),
-                    );
-                    for day in &days {
-                        let hours = self.get_issue_or_zero(day, *issue);
-                        cols.push(
-                            hours
-                                .fmt_zero_empty()
-                                .cell()
-                                .foreground_color(fg)
-                                .justify(Justify::Right),
-                        )
-                    }
-                    rows.push(cols.row());
-                }
-            }
-        }
-        let mut last_row = vec!["∑".cell()];
-        last_row.push(
-            total_hours
-                .cell()
-                .justify(Justify::Right)
-                .foreground_color(Some(Color::Cyan)),
-        );
-        for day in &days {
-            let hours_at_day = *self.hours_at.get(day).unwrap_or(&0.0);
-            let color = match hours_at_day {
-                _ if hours_at_day <= 8.0 => Color::Green,
-                _ if hours_at_day <= 10.0 => Color::Yellow,
-                _ => Color::Red,
-            };
-            last_row.push(
-                hours_at_day
-                    .fmt_zero_empty()
-                    .cell()
-                    .justify(Justify::Right)
-                    .foreground_color(Some(color)),
-            );
-        }
-
-        rows.push(last_row.row());
-        rows.table()
+        projects
     }
 }
 
-/// A [DailyReport] represents a special report for a single day.
-#[derive(Debug)]
-pub struct DailyReport {
-    entries: Vec<TimeEntry>,
-    total_hours: f64,
+/// A [Summary] is the plain result of cumulating a [Report] over a list
+/// of [Column]s, independent of how it is displayed.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub columns: Vec<Column>,
+    /// The projects sorted by their name.
+    pub projects: Vec<ProjectRow>,
+    /// The hours of all projects per column.
+    pub totals: Vec<Minutes>,
+    pub total: Minutes,
 }
 
-impl DailyReport {
-    /// Converts a `DailyReport` into a `Table` for display.
-    ///
-    /// # Example
-    ///
-    /// Given the following `DailyReport`:
-    ///
-    /// ```
-    /// let daily_report = DailyReport {
-    ///     entries: vec![
-    ///         TimeEntry {
-    ///             id: 1,
-    ///             user: Named { id: 1, name: "User A".to_owned() },
-    ///             project: Named { id: 1, name: "Project A".to_owned() },
-    ///             issue: Some(Named { id: 1234, name: "Issue A".to_owned() }),
-    ///             hours: 9.0,
-    ///             comments: Some("Worked on issue A".to_owned()),
-    ///             spent_on: "2022-01-01".to_owned(),
-    ///         },
-    ///         TimeEntry {
-    ///             id: 2,
-    ///             user: Named { id: 1, name: "User A".to_owned() },
-    ///             project: Named { id: 2, name: "Project B".to_owned() },
-    ///             issue: None,
-    ///             hours: 3.0,
-    ///             comments: None,
-    ///             spent_on: "2022-01-01".to_owned(),
-    ///         },
-    ///     ],
-    ///     total_hours: 12.0,
-    /// };
-    /// ```
-    ///
-    /// Calling `to_table_struct` on the `DailyReport` will produce more or less the following `Table`:
-    ///
-    /// ```text
-    /// +---------+----------+-------+---------------+
-    /// | Project | Issue    | Hours | Comment       |
-    /// +---------+----------+-------+---------------+
-    /// | Project A | #1234  | 9.00 | Worked on issue A |
-    /// | Project B |        | 3.00 |              |
-    /// +---------+--------+-------+---------------+
-    /// ```
-    pub fn to_table_struct(&self) -> TableStruct {
-        let headers = vec![
-            "Project".cell().bold(true),
-            "Issue".cell().bold(true),
-            "Subject".cell().bold(true),
-            format!("Hours (∑ {:.2})", self.total_hours)
-                .cell()
-                .bold(true),
-            "Comment".cell().bold(true),
-        ];
-
-        let mut rows = vec![];
-        rows.push(headers.row());
-        let mut actual_entries = self.entries.clone();
-        actual_entries.reverse();
-        for entry in &actual_entries {
-            let cells = vec![
-                entry.project.name.as_ref().unwrap_or(&"".into()).cell(),
-                entry
-                    .issue
-                    .as_ref()
-                    .and_then(|x| x.name.clone())
-                    .unwrap_or("".to_owned())
-                    .cell(),
-                entry
-                    .issue
-                    .as_ref()
-                    .map(|issue| format!("#{}", issue.id))
-                    .unwrap_or("".into())
-                    .cell()
-                    .foreground_color(Some(Color::Cyan))
-                    .justify(Justify::Right),
-                format!("{:.2}", entry.hours).cell().justify(Justify::Right),
-                entry
-                    .comments
-                    .as_ref()
-                    .unwrap_or(&"".into())
-                    .to_string()
-                    .cell()
-                    .foreground_color(Some(Color::Rgb(230, 230, 230))),
-            ];
-            rows.push(cells.row());
-        }
-
-        rows.table()
-    }
+/// The hours of a single project in a [Summary].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProjectRow {
+    pub id: i32,
+    pub name: String,
+    pub total: Minutes,
+    /// The hours per column of the [Summary].
+    pub hours: Vec<Minutes>,
+    /// The hours per column booked on this project without an issue.
+    pub without_issue: Vec<Minutes>,
+    pub issues: Vec<IssueRow>,
 }
 
-trait NaiveDateExt {
-    fn monday_of_week(&self) -> Option<NaiveDate>;
-
-    fn sunday_of_week(&self) -> Option<NaiveDate>;
+/// The hours of a single issue in a [Summary].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct IssueRow {
+    pub id: i32,
+    pub subject: String,
+    pub total: Minutes,
+    /// The hours per column of the [Summary].
+    pub hours: Vec<Minutes>,
 }
 
-impl NaiveDateExt for NaiveDate {
-    fn monday_of_week(&self) -> Option<NaiveDate> {
-        let diff = self.weekday().num_days_from_monday();
-        self.checked_sub_days(Days::new(diff as u64))
-    }
-
-    fn sunday_of_week(&self) -> Option<NaiveDate> {
-        let diff = self.weekday().num_days_from_sunday();
-        self.checked_add_days(Days::new((7 - diff) as u64))
-    }
+/// A [SearchResult] is an issue found by a search.
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchResult {
+    pub id: i32,
+    pub subject: String,
+    pub project: Option<String>,
+    pub status: Option<String>,
+    pub url: String,
 }
 
-trait DisplayExt {
-    fn fmt_zero_empty(self) -> String;
-}
-
-impl DisplayExt for f64 {
-    fn fmt_zero_empty(self) -> String {
-        if self == 0.0 {
-            "".into()
-        } else {
-            self.to_string()
-        }
-    }
+/// A [DailyReport] represents a special report for a single day.
+#[derive(Serialize, Debug)]
+pub struct DailyReport {
+    /// The time entries of the day, most recent first.
+    pub entries: Vec<TimeEntry>,
+    pub total_hours: Minutes,
 }
 
 #[cfg(test)]
 mod tests {
     use crate::redmine::Named;
+    use crate::track::period::Period;
 
     use super::*;
 
@@ -366,7 +299,7 @@ mod tests {
     fn test_days_till_sunday() {
         let monday = NaiveDate::from_ymd_opt(2023, 2, 16).unwrap();
         let funday = NaiveDate::from_ymd_opt(2023, 2, 19).unwrap();
-        assert_eq!(monday.sunday_of_week(), Some(funday));
+        assert_eq!(Period::week(monday).to(), funday);
     }
 
     #[test]
@@ -389,7 +322,10 @@ mod tests {
         let expected_cumulative_hours = vec![((day1, 1), 4.0), ((day2, 2), 2.0), ((day3, 1), 5.0)];
 
         for (key, value) in expected_cumulative_hours {
-            assert_eq!(result.cumulative_hours.get(&key), Some(&value));
+            assert_eq!(
+                result.cumulative_hours.get(&key),
+                Some(&Minutes::from_hours(value))
+            );
         }
     }
 
@@ -407,8 +343,8 @@ mod tests {
 
         let result = Report::from_entries(&time_entries, &vec![]);
 
-        let sum_of_hours_per_project = result.hours_per_project.values().sum::<f64>();
-        let sum_of_hours_per_weekday = result.hours_at.values().sum::<f64>();
+        let sum_of_hours_per_project = result.hours_per_project.values().sum::<Minutes>();
+        let sum_of_hours_per_weekday = result.hours_at.values().sum::<Minutes>();
 
         assert_eq!(sum_of_hours_per_project, sum_of_hours_per_weekday);
     }
@@ -432,12 +368,70 @@ mod tests {
         let report = Report::from_entries(&time_entries, &vec![]);
         let daily_report = report.get_report_for_date(&day1);
 
-        let expected_total_hours = 9.0;
+        let expected_total_hours = Minutes::from_hours(9.0);
         assert_eq!(daily_report.total_hours, expected_total_hours);
 
         assert_eq!(daily_report.entries, day1_entries);
     }
 
+    #[test]
+    fn test_summary_has_hours_per_column() {
+        let day1 = NaiveDate::from_ymd_opt(2022, 1, 3).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2022, 1, 5).unwrap();
+
+        let time_entries = vec![
+            time_entry(1, (1, "John Doe"), (2, "Project B"), 4.0, &day1),
+            time_entry(2, (1, "John Doe"), (1, "Project A"), 5.0, &day2),
+            time_entry(3, (1, "John Doe"), (1, "Project A"), 1.0, &day1),
+        ];
+
+        let report = Report::from_entries(&time_entries, &vec![]);
+        let summary = report.summary(&Period::week(day1).columns());
+
+        let names: Vec<&str> = summary.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Project A", "Project B"]);
+        let hours = |hours: i64| Minutes::new(hours * 60);
+        assert_eq!(summary.projects[0].total, hours(6));
+        assert_eq!(
+            summary.projects[0].hours[..3],
+            [hours(1), hours(0), hours(5)]
+        );
+        assert_eq!(summary.totals[..3], [hours(5), hours(0), hours(5)]);
+        assert_eq!(summary.total, hours(10));
+    }
+
+    #[test]
+    fn test_summary_splits_hours_without_issue() {
+        let day = NaiveDate::from_ymd_opt(2022, 1, 3).unwrap();
+        let moved = Issue {
+            id: 1234,
+            subject: "Login".to_string(),
+            project: Named {
+                id: 2,
+                name: Some("Project B".to_string()),
+            },
+            status: None,
+            assigned_to: None,
+            updated_on: None,
+        };
+        let mut on_issue = time_entry(1, (1, "John Doe"), (1, "Project A"), 3.0, &day);
+        on_issue.issue = Some(Named {
+            id: moved.id,
+            name: None,
+        });
+        let time_entries = vec![
+            on_issue,
+            time_entry(2, (1, "John Doe"), (1, "Project A"), 1.0, &day),
+        ];
+
+        let report = Report::from_entries(&time_entries, &vec![moved]);
+        let summary = report.summary(&Period::week(day).columns());
+
+        let hours = |hours: i64| Minutes::new(hours * 60);
+        assert_eq!(summary.projects[0].hours[0], hours(4));
+        assert_eq!(summary.projects[0].without_issue[0], hours(1));
+    }
+
     fn time_entry(
         id: i32,
         user: (i32, &str),
@@ -456,7 +450,8 @@ mod tests {
                 name: Some(project.1.to_string()),
             },
             issue: None,
-            hours,
+            activity: None,
+            hours: Minutes::from_hours(hours),
             comments: None,
             spent_on: spent_on.format("%Y-%m-%d").to_string(),
             custom_fields: vec![],
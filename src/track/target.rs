@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use url::Url;
+
+/// A [Target] is a reference to the issue or project to book on, as
+/// typed or pasted by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The id of an issue.
+    Issue(i32),
+    /// The identifier or id of a project.
+    Project(String),
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    /// Parses an issue like `1234`, `#1234` or
+    /// `https://redmine.example.com/issues/1234` or a project like
+    /// `@identifier` or `https://redmine.example.com/projects/identifier`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        let invalid = || {
+            format!(
+                "{} is neither an issue like 1234, #1234 or an issue URL, nor a project like @identifier.",
+                input
+            )
+        };
+
+        if let Some(project) = input.strip_prefix('@') {
+            return match project.is_empty() || project.contains(char::is_whitespace) {
+                true => Err(invalid()),
+                false => Ok(Target::Project(project.to_string())),
+            };
+        }
+
+        let id = input.strip_prefix('#').unwrap_or(input);
+        if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) {
+            return id.parse().map(Target::Issue).map_err(|_| invalid());
+        }
+
+        let url = Url::parse(input).map_err(|_| invalid())?;
+        let segments: Vec<&str> = url.path_segments().map(|s| s.collect()).unwrap_or_default();
+        segments
+            .windows(2)
+            .rev()
+            .find_map(|pair| match pair {
+                ["issues", id] => id.trim_end_matches(".json").parse().ok().map(Target::Issue),
+                ["projects", identifier] if !identifier.is_empty() => {
+                    Some(Target::Project(identifier.to_string()))
+                }
+                _ => None,
+            })
+            .ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Target, String> {
+        input.parse()
+    }
+
+    #[test]
+    fn test_parse_issues() {
+        assert_eq!(parse("1234"), Ok(Target::Issue(1234)));
+        assert_eq!(parse(" #1234 "), Ok(Target::Issue(1234)));
+        assert_eq!(
+            parse("https://redmine.example.com/issues/1234"),
+            Ok(Target::Issue(1234))
+        );
+        assert_eq!(
+            parse("https://example.com/redmine/issues/1234?tab=history#note-3"),
+            Ok(Target::Issue(1234))
+        );
+    }
+
+    #[test]
+    fn test_parse_projects() {
+        assert_eq!(
+            parse("@my-project"),
+            Ok(Target::Project("my-project".to_string()))
+        );
+        assert_eq!(
+            parse("https://redmine.example.com/projects/my-project/issues"),
+            Ok(Target::Project("my-project".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse("abc1").is_err());
+        assert!(parse("#").is_err());
+        assert!(parse("@").is_err());
+        assert!(parse("99999999999").is_err());
+        assert!(parse("https://redmine.example.com/my/page").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::redmine::Named;
+use crate::track::Error;
+
+/// A running [Timer], started with `track start`, which survives
+/// restarting the shell.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Timer {
+    pub issue_id: Option<i32>,
+    pub project: Option<Named>,
+    pub started_at: DateTime<Local>,
+    pub comment: Option<String>,
+}
+
+impl Timer {
+    /// Load the running timer from `~/.track_timer`, if there is one.
+    pub fn load() -> Result<Option<Self>, Error> {
+        super::load_json(".track_timer")
+    }
+
+    /// Store this timer in `~/.track_timer`.
+    pub fn store(&self) -> Result<(), Error> {
+        super::store_json(".track_timer", self)
+    }
+
+    /// Remove the running timer from `~/.track_timer`.
+    pub fn clear() -> Result<(), Error> {
+        super::remove_file(".track_timer")
+    }
+
+    /// Returns a short, human readable description of what is tracked.
+    pub fn target(&self) -> String {
+        match (&self.issue_id, &self.project) {
+            (Some(issue_id), _) => format!("#{}", issue_id),
+            (None, Some(project)) => project.name.clone().unwrap_or(project.id.to_string()),
+            (None, None) => "nothing".to_string(),
+        }
+    }
+
+    /// Returns the comment for the time entry, starting with the time
+    /// range from the start of this timer until `now`.
+    pub fn comment_until(&self, now: DateTime<Local>) -> String {
+        let range = format!(
+            "{} - {}",
+            self.started_at.format("%H:%M"),
+            now.format("%H:%M")
+        );
+        match &self.comment {
+            Some(comment) if !comment.is_empty() => format!("{} {}", range, comment),
+            _ => range,
+        }
+    }
+}
@@ -1,38 +1,50 @@
 use anyhow::anyhow;
-use dialoguer::{theme::ColorfulTheme, Confirm, Input};
-use regex::Regex;
-use std::str::FromStr;
+use chrono::Local;
+use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input, MultiSelect};
 
-use crate::redmine::{Activities, Activity, CustomField, CustomValue, Project, Projects};
+use crate::redmine::{Activities, Activity, CustomField, CustomValue, FieldValue, Issue, Project};
+use crate::track::duration::{DurationFormat, HoursInput, Minutes};
+use crate::track::report::SearchResult;
+use crate::track::target::Target;
+use crate::track::{comment, field, project};
 
-pub fn select_project(projects: Projects) -> Option<Project> {
-    let selections: Vec<String> = projects.projects.iter().map(|p| p.name.clone()).collect();
+/// Asks the user to select one of the given `projects`, which are shown
+/// in the given order, with their path and identifier.
+pub fn select_project(projects: Vec<Project>) -> Option<Project> {
+    let selections: Vec<String> = projects
+        .iter()
+        .map(|p| format!("{} ({})", project::path(p, &projects), p.identifier))
+        .collect();
 
-    let selection = dialoguer::FuzzySelect::with_theme(&ColorfulTheme::default())
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
         .with_prompt("Please choose the project")
         .items(&selections[..])
         .default(0)
         .interact_opt()
         .unwrap();
 
-    if let Some(selection) = selection {
-        Some(projects.projects[selection].clone())
-    } else {
-        None
-    }
+    selection.map(|selection| projects[selection].clone())
 }
 
-pub fn select_activity(activities: Activities) -> Activity {
+/// Asks the user to select one of the given `activities`.
+///
+/// The `current` activity is preselected, if given, otherwise the
+/// default activity of Redmine.
+pub fn select_activity(activities: Activities, current: Option<i32>) -> Activity {
     let selections: Vec<String> = activities
         .activities
         .iter()
         .map(|a| a.name.clone())
         .collect();
 
-    let default = activities
-        .activities
-        .iter()
-        .position(|a| a.is_default.unwrap_or(false))
+    let default = current
+        .and_then(|id| activities.activities.iter().position(|a| a.id == id))
+        .or_else(|| {
+            activities
+                .activities
+                .iter()
+                .position(|a| a.is_default.unwrap_or(false))
+        })
         .unwrap_or(0);
 
     let selection = dialoguer::Select::new()
@@ -45,73 +57,265 @@ pub fn select_activity(activities: Activities) -> Activity {
     activities.activities[selection].clone()
 }
 
-pub fn ask_for_issue() -> Option<i32> {
-    let i: String = Input::new()
-        .with_prompt("Issue (leave empty for project only)")
+/// Asks the user to type an issue like `1234`, `#1234` or an issue URL,
+/// or a project like `@identifier`, prefilled with the `initial` issue.
+///
+/// Returns [None] to choose from a list instead.
+pub fn type_target(initial: Option<i32>) -> Option<Target> {
+    let mut target = None;
+    Input::<String>::new()
+        .with_prompt("Issue or @project (leave empty to choose)")
+        .with_initial_text(initial.map(|i| i.to_string()).unwrap_or_default())
         .allow_empty(true)
         .validate_with(|v: &String| {
-            let re = Regex::new(r"\d+").unwrap();
-            if v.is_empty() || re.is_match(v) {
-                Ok(())
-            } else {
-                Err("Please insert a valid issue number.")
-            }
+            target = match v.trim() {
+                "" => None,
+                v => Some(v.parse::<Target>()?),
+            };
+            Ok::<(), String>(())
         })
         .interact()
         .unwrap();
 
-    i32::from_str(&i).ok()
+    target
+}
+
+/// Asks the user to choose one of the given `issues`.
+///
+/// Returns [None] to choose a project from a list.
+pub fn select_issue(issues: &[Issue]) -> Option<&Issue> {
+    if issues.is_empty() {
+        return None;
+    }
+
+    let selections: Vec<String> = issues
+        .iter()
+        .map(|i| {
+            format!(
+                "#{} {} ({})",
+                i.id,
+                i.subject,
+                i.project.name.as_deref().unwrap_or_default()
+            )
+        })
+        .collect();
+
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Issue (esc for project only)")
+        .items(&selections[..])
+        .default(0)
+        .interact_opt()
+        .unwrap();
+
+    selection.map(|selection| &issues[selection])
 }
 
-pub fn ask_for_comment() -> String {
-    Input::new().with_prompt("Comment").interact().unwrap()
+/// Asks for the comment, rejecting overlapping time ranges.
+pub fn ask_for_comment(initial: Option<&str>) -> String {
+    Input::new()
+        .with_prompt("Comment")
+        .with_initial_text(initial.unwrap_or_default())
+        .validate_with(|v: &String| comment::time_ranges(v).map(|_| ()))
+        .interact()
+        .unwrap()
 }
 
-pub fn ask_for_hours() -> f64 {
-    Input::new().with_prompt("Hours").interact().unwrap()
+/// Asks for the hours, like `1.5`, `1,5`, `1:30`, `1h30m` or `90m`.
+///
+/// Adjustments like `+15m` or `-0:30` are applied to the `initial`
+/// hours, which are prefilled in the given `format`. The input is echoed
+/// with the resulting decimal hours, like `Hours: 1h45m = 1.75h`.
+pub fn ask_for_hours(initial: Option<Minutes>, format: DurationFormat) -> Minutes {
+    let base = initial.unwrap_or(Minutes::ZERO);
+    let mut hours = base;
+    let input: String = Input::new()
+        .with_prompt("Hours")
+        .with_initial_text(initial.map(|h| h.format(format)).unwrap_or_default())
+        .validate_with(|v: &String| {
+            hours = v.parse::<HoursInput>()?.resolve(base)?;
+            Ok::<(), String>(())
+        })
+        .report(false)
+        .interact()
+        .unwrap();
+
+    let decimal = hours.format(DurationFormat::Decimal);
+    match input.trim() == decimal {
+        true => println!("Hours: {}", decimal),
+        false => println!("Hours: {} = {}h", input.trim(), decimal),
+    }
+
+    hours
 }
 
-pub fn ask_for_custom_field(field: CustomField) -> anyhow::Result<Option<CustomValue>> {
-    match &*field.field_format {
+/// Asks the user to choose one of the search `results`.
+pub fn select_search_result(results: &[SearchResult]) -> Option<&SearchResult> {
+    if results.is_empty() {
+        println!("No issues found.");
+        return None;
+    }
+
+    let selections: Vec<String> = results
+        .iter()
+        .map(|r| {
+            let details: Vec<&str> = [r.project.as_deref(), r.status.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect();
+            format!("#{} {} ({})", r.id, r.subject, details.join(", "))
+        })
+        .collect();
+
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Please choose the issue")
+        .items(&selections[..])
+        .default(0)
+        .interact_opt()
+        .unwrap();
+
+    selection.map(|selection| &results[selection])
+}
+
+/// Asks the user to choose one of the given `options` and returns its
+/// index.
+pub fn choose(prompt: &str, options: &[String]) -> usize {
+    dialoguer::Select::new()
+        .with_prompt(prompt)
+        .items(options)
+        .default(0)
+        .interact()
+        .unwrap()
+}
+
+/// Asks the user to choose any of the given `options` and returns their
+/// indices.
+pub fn choose_many(prompt: &str, options: &[String]) -> anyhow::Result<Vec<usize>> {
+    Ok(MultiSelect::new()
+        .with_prompt(format!("{} (space to select)", prompt))
+        .items(options)
+        .interact()?)
+}
+
+pub fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    Ok(Confirm::new().with_prompt(prompt).default(false).interact()?)
+}
+
+/// Asks the user for a value of the given custom `field`, prefilled
+/// with the `current` value or the default value of the field.
+///
+/// Fields with possible values are chosen from a list, all others are
+/// typed and validated according to their format. Optional fields may
+/// be left empty, resulting in [None].
+pub fn ask_for_custom_field(
+    field: CustomField,
+    current: Option<FieldValue>,
+) -> anyhow::Result<Option<CustomValue>> {
+    let current: Vec<String> = match current {
+        Some(value) => value.values().iter().map(|v| v.to_string()).collect(),
+        None => field
+            .default_value()
+            .map(|v| v.to_string())
+            .into_iter()
+            .collect(),
+    };
+
+    let value = match &*field.field_format {
         "bool" => {
-            let field_name = field.name.clone();
-            let result = Confirm::new().with_prompt(&field_name).interact()?;
-
-            Ok(Some(CustomValue {
-                id: field.id,
-                name: field_name,
-                value: Some(match result {
-                    true => "1".to_string(),
-                    false => "0".to_string(),
-                }),
-            }))
+            let result = Confirm::new()
+                .with_prompt(&field.name)
+                .default(current.first().map(|v| v.as_str()) == Some("1"))
+                .interact()?;
+            Some(FieldValue::Single(
+                match result {
+                    true => "1",
+                    false => "0",
+                }
+                .to_string(),
+            ))
         }
 
-        "string" => {
-            let field_name = field.name.clone();
-            let result = Input::new().with_prompt(&field_name).interact()?;
-            Ok(Some(CustomValue {
-                id: field.id,
-                name: field_name,
-                value: Some(result),
-            }))
+        "attachment" => {
+            return Err(anyhow!(
+                "The format attachment of {} is not supported, sorry.",
+                field.name
+            ))
         }
 
-        format => Err(anyhow!("The format {} is unknown, sorry.", format)),
-    }
-}
+        _ if !field.possible_values.is_empty() && field.is_multiple() => {
+            let labels: Vec<&str> = field.possible_values.iter().map(|v| v.label()).collect();
+            let defaults: Vec<bool> = field
+                .possible_values
+                .iter()
+                .map(|v| current.contains(&v.value))
+                .collect();
+            let selection = MultiSelect::new()
+                .with_prompt(format!("{} (space to select)", field.name))
+                .items(&labels)
+                .defaults(&defaults)
+                .interact()?;
+            let values: Vec<String> = selection
+                .into_iter()
+                .map(|i| field.possible_values[i].value.clone())
+                .collect();
+            match values.is_empty() {
+                true if field.is_required() => {
+                    return Err(anyhow!("{} requires at least one value.", field.name))
+                }
+                true => None,
+                false => Some(FieldValue::Multiple(values)),
+            }
+        }
 
-pub fn analyze_comments(input: String) -> Option<(chrono::NaiveTime, chrono::NaiveTime)> {
-    let re = Regex::new(r"\s*\d\d?:\d{2}\s*-\s*\d\d?:\d{2}").ok();
-    let re = match re {
-        None => return None,
-        Some(re) => re,
+        _ if !field.possible_values.is_empty() => {
+            let labels: Vec<&str> = field.possible_values.iter().map(|v| v.label()).collect();
+            let default = current
+                .first()
+                .and_then(|c| field.possible_values.iter().position(|v| &v.value == c))
+                .unwrap_or(0);
+            let prompt = match field.is_required() {
+                true => field.name.clone(),
+                false => format!("{} (esc to leave empty)", field.name),
+            };
+            let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt)
+                .items(&labels)
+                .default(default)
+                .interact_opt()?;
+            match selection {
+                Some(i) => Some(FieldValue::Single(field.possible_values[i].value.clone())),
+                None if field.is_required() => {
+                    return Err(anyhow!("{} requires a value.", field.name))
+                }
+                None => None,
+            }
+        }
+
+        _ => {
+            let today = Local::now().date_naive();
+            let hint = match &*field.field_format {
+                "int" | "float" => " (number)",
+                "date" => " (date)",
+                "user" => " (user id)",
+                "version" => " (version id)",
+                _ => "",
+            };
+            let mut value = None;
+            Input::<String>::new()
+                .with_prompt(format!("{}{}", field.name, hint))
+                .with_initial_text(current.join(","))
+                .allow_empty(!field.is_required())
+                .validate_with(|v: &String| {
+                    value = field::parse(&field, v, today)?;
+                    Ok::<(), String>(())
+                })
+                .interact()?;
+            value
+        }
     };
 
-    let m = re.find(&input)?;
-    let filtered: String = m.as_str().chars().filter(|c| !c.is_whitespace()).collect();
-    let result: Vec<&str> = filtered.as_str().split('-').collect();
-    let left = chrono::NaiveTime::parse_from_str(result[0], "%H:%M").ok();
-    let right = chrono::NaiveTime::parse_from_str(result[1], "%H:%M").ok();
-    left.and_then(|l| right.map(|r| (l, r)))
+    Ok(value.map(|value| CustomValue {
+        id: field.id,
+        name: field.name,
+        value: Some(value),
+    }))
 }